@@ -1,78 +1,67 @@
-use std::{cmp::Ordering, io::Read};
+use std::io::Read;
+
+mod scanner;
+
+use scanner::Scanner;
+
+const DIGITS: [(&str, u32); 9] = [
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+];
+
+const SPELLED_DIGITS: [(&str, u32); 9] = [
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+// the first and last token of the line combined into a two-digit number,
+// or None if the line does not contain any token (e.g. empty line)
+fn calibration_value(scanner: &Scanner<u32>, line: &str) -> Option<u32> {
+    // matches are ordered by their end, but tokens can have different lengths
+    let mut matches = scanner.find_overlapping(line);
+    let first = matches.next()?;
+    let (first, last) = matches.fold((first, first), |(first, last), m| {
+        (
+            if m.start < first.start { m } else { first },
+            if m.start > last.start { m } else { last },
+        )
+    });
+
+    Some(first.value * 10 + last.value)
+}
+
+fn calibration_sum(input: &str, scanner: &Scanner<u32>) -> u32 {
+    input
+        .lines()
+        .filter_map(|line| calibration_value(scanner, line))
+        .sum()
+}
 
 #[allow(unused)]
 fn calibration_sum_part1(input: &str) -> u32 {
-    let mut sum = 0;
-    for line in input.lines() {
-        let mut it = line.chars().filter_map(|c| c.to_digit(10));
-        if let Some(first) = it.next() {
-            let last = it.last().unwrap_or(first);
-
-            sum += first * 10 + last;
-        }
-        // else continue to next line, as the line did not contain any digits (e.g. empty line)
-    }
-    sum
+    calibration_sum(input, &Scanner::new(DIGITS))
 }
 
 #[allow(unused)]
 fn calibration_sum_part2(input: &str) -> u32 {
-    let spelled_to_digit = [
-        ("one", 1),
-        ("two", 2),
-        ("three", 3),
-        ("four", 4),
-        ("five", 5),
-        ("six", 6),
-        ("seven", 7),
-        ("eight", 8),
-        ("nine", 9),
-    ];
-
-    let mut sum = 0;
-    for line in input.lines() {
-        let mut it = line
-            .chars()
-            .enumerate()
-            .filter_map(|(i, c)| c.to_digit(10).map(|d| (i, d)));
-
-        let first_char = it.next();
-        let last_char = it.last().or(first_char);
-
-        let first_spelled = spelled_to_digit
-            .iter()
-            .filter_map(|(s, d)| line.find(s).map(|i| (i, *d)))
-            .min();
-        let last_spelled = spelled_to_digit
-            .iter()
-            .filter_map(|(s, d)| line.rfind(s).map(|i| (i, *d)))
-            .max();
-
-        let first_digit = match (first_char, first_spelled) {
-            (None, None) => continue,
-            (None, Some((_, d))) => d,
-            (Some((_, d)), None) => d,
-            (Some((i1, d1)), Some((i2, d2))) => match i1.cmp(&i2) {
-                Ordering::Less => d1,
-                Ordering::Equal => panic!(),
-                Ordering::Greater => d2,
-            },
-        };
-
-        let last_digit = match (last_char, last_spelled) {
-            (None, None) => continue,
-            (None, Some((_, d))) => d,
-            (Some((_, d)), None) => d,
-            (Some((i1, d1)), Some((i2, d2))) => match i1.cmp(&i2) {
-                Ordering::Less => d2,
-                Ordering::Equal => panic!(),
-                Ordering::Greater => d1,
-            },
-        };
-
-        sum += first_digit * 10 + last_digit;
-    }
-    sum
+    calibration_sum(
+        input,
+        &Scanner::new(DIGITS.into_iter().chain(SPELLED_DIGITS)),
+    )
 }
 
 fn main() -> Result<(), std::io::Error> {
@@ -121,4 +110,47 @@ zoneight234
 
         assert_eq!(expected, result);
     }
+
+    #[test]
+    fn test_overlapping() {
+        let scanner = Scanner::new(SPELLED_DIGITS);
+
+        let expected = vec![(0, 5, 8), (4, 7, 2)];
+        let actual: Vec<_> = scanner
+            .find_overlapping("eightwo")
+            .map(|m| (m.start, m.end, *m.value))
+            .collect();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_nested_patterns() {
+        let scanner = Scanner::new([("he", 0), ("she", 1), ("his", 2), ("hers", 3)]);
+
+        let expected = vec![(1, 4, 1), (2, 4, 0), (2, 6, 3)];
+        let actual: Vec<_> = scanner
+            .find_overlapping("ushers")
+            .map(|m| (m.start, m.end, *m.value))
+            .collect();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_custom_words() {
+        let scanner = Scanner::new(DIGITS.into_iter().chain([
+            ("eins", 1),
+            ("zwei", 2),
+            ("drei", 3),
+            ("vier", 4),
+            ("neun", 9),
+        ]));
+
+        assert_eq!(Some(13), calibration_value(&scanner, "einsxdrei"));
+        assert_eq!(Some(29), calibration_value(&scanner, "zweineun"));
+        assert_eq!(Some(44), calibration_value(&scanner, "vier"));
+        assert_eq!(Some(57), calibration_value(&scanner, "5sieben7"));
+        assert_eq!(None, calibration_value(&scanner, "one"));
+    }
 }
@@ -0,0 +1,147 @@
+use std::collections::{HashMap, VecDeque};
+
+const ROOT: usize = 0;
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    children: HashMap<u8, usize>,
+    // the longest proper suffix of this node that is also a node
+    fail: usize,
+    // indices of every pattern that ends in this node, including those reached via `fail`
+    outputs: Vec<usize>,
+}
+
+// Finds every occurrence of a fixed set of patterns in a single pass over the input,
+// including overlapping ones, e.g. both `eight` and `two` in `eightwo`.
+//
+// This is the Aho-Corasick automaton: a trie of all patterns, where every node also knows where to
+// continue when the next byte does not match, so the input never has to be read twice.
+#[derive(Debug, Clone)]
+pub struct Scanner<V> {
+    nodes: Vec<Node>,
+    patterns: Vec<(usize, V)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'s, V> {
+    pub start: usize,
+    pub end: usize,
+    pub value: &'s V,
+}
+
+impl<V> Scanner<V> {
+    // panics on empty patterns, as they would match everywhere
+    pub fn new<'p>(patterns: impl IntoIterator<Item = (&'p str, V)>) -> Self {
+        let mut nodes = vec![Node::default()];
+        let mut values = Vec::new();
+
+        for (pattern, value) in patterns {
+            assert!(!pattern.is_empty(), "patterns must not be empty");
+
+            let mut node = ROOT;
+            for &b in pattern.as_bytes() {
+                node = match nodes[node].children.get(&b) {
+                    Some(&child) => child,
+                    None => {
+                        nodes.push(Node::default());
+                        let child = nodes.len() - 1;
+                        nodes[node].children.insert(b, child);
+                        child
+                    }
+                };
+            }
+
+            nodes[node].outputs.push(values.len());
+            values.push((pattern.len(), value));
+        }
+
+        // breadth first, so the fail link of every parent is known before its children
+        let mut queue: VecDeque<usize> = nodes[ROOT].children.values().copied().collect();
+
+        while let Some(node) = queue.pop_front() {
+            let children: Vec<(u8, usize)> =
+                nodes[node].children.iter().map(|(&b, &c)| (b, c)).collect();
+
+            for (b, child) in children {
+                let mut fail = nodes[node].fail;
+                let target = loop {
+                    if let Some(&next) = nodes[fail].children.get(&b) {
+                        break next;
+                    }
+                    if fail == ROOT {
+                        break ROOT;
+                    }
+                    fail = nodes[fail].fail;
+                };
+
+                nodes[child].fail = target;
+                let inherited = nodes[target].outputs.clone();
+                nodes[child].outputs.extend(inherited);
+
+                queue.push_back(child);
+            }
+        }
+
+        Self {
+            nodes,
+            patterns: values,
+        }
+    }
+
+    fn step(&self, mut node: usize, b: u8) -> usize {
+        loop {
+            if let Some(&next) = self.nodes[node].children.get(&b) {
+                return next;
+            }
+            if node == ROOT {
+                return ROOT;
+            }
+            node = self.nodes[node].fail;
+        }
+    }
+
+    // every match, ordered by where it ends, positions are byte offsets into `haystack`
+    pub fn find_overlapping<'s, 'h>(&'s self, haystack: &'h str) -> FindOverlapping<'s, 'h, V> {
+        FindOverlapping {
+            scanner: self,
+            haystack: haystack.as_bytes(),
+            position: 0,
+            node: ROOT,
+            output: 0,
+        }
+    }
+}
+
+pub struct FindOverlapping<'s, 'h, V> {
+    scanner: &'s Scanner<V>,
+    haystack: &'h [u8],
+    // the number of bytes consumed so far
+    position: usize,
+    node: usize,
+    // the next output of `node` to report
+    output: usize,
+}
+
+impl<'s, V> Iterator for FindOverlapping<'s, '_, V> {
+    type Item = Match<'s, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(&i) = self.scanner.nodes[self.node].outputs.get(self.output) {
+                self.output += 1;
+
+                let (len, ref value) = self.scanner.patterns[i];
+                return Some(Match {
+                    start: self.position - len,
+                    end: self.position,
+                    value,
+                });
+            }
+
+            let &b = self.haystack.get(self.position)?;
+            self.node = self.scanner.step(self.node, b);
+            self.position += 1;
+            self.output = 0;
+        }
+    }
+}
@@ -1,4 +1,6 @@
+use std::collections::HashSet;
 use std::io::Read;
+use utils::geom::polygon;
 use utils::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -14,8 +16,6 @@ enum Tile {
     Start,
     Ground,
     Pipe(Direction, Direction),
-    Inner,
-    Outer,
 }
 
 impl TryFrom<char> for Tile {
@@ -39,19 +39,7 @@ impl TryFrom<char> for Tile {
 type Grid = grid::Grid<Tile>;
 
 fn find_and_replace_s(grid: &mut Grid) -> (usize, usize) {
-    // find S
-    let find_s = || {
-        for y in 0..grid.rows() {
-            for x in 0..grid.columns() {
-                if grid[(x, y)] == Tile::Start {
-                    return Some((x, y));
-                }
-            }
-        }
-        None
-    };
-
-    let (x, y) = find_s().expect("found no S");
+    let (x, y) = grid.position(|&t| t == Tile::Start).expect("found no S");
 
     // find neighbours
     let (mut d1, mut d2) = (None, None);
@@ -151,91 +139,112 @@ fn find_loop(grid: &Grid, start: (usize, usize)) -> (Vec<(usize, usize)>, Vec<Ti
     (coords, tiles)
 }
 
-fn part1(input: &str) -> usize {
-    let mut grid = Grid::try_from(input).unwrap();
-
-    let start = find_and_replace_s(&mut grid);
-
-    let (l, _) = find_loop(&grid, start);
-
-    l.len() / 2
+// the main loop and everything it encloses
+#[derive(Debug, Clone)]
+struct LoopAnalysis {
+    // the grid with `S` replaced by the pipe it stands for
+    grid: Grid,
+    // every tile of the loop in order, beginning at `S`
+    path: Vec<(usize, usize)>,
+    enclosed: HashSet<(usize, usize)>,
 }
 
-fn part2(input: &str) -> usize {
+fn analyze(input: &str) -> LoopAnalysis {
     let mut grid = Grid::try_from(input).unwrap();
 
     let start = find_and_replace_s(&mut grid);
 
-    let (cs, ts) = find_loop(&grid, start);
+    let (path, _) = find_loop(&grid, start);
+    let on_loop: HashSet<(usize, usize)> = path.iter().copied().collect();
 
-    // convert all non-loop pipes into ground tiles
+    // only needed to draw the enclosed tiles, `part2` counts them with Pick's theorem
     //
-    // first convert everything to ground
-    for tile in grid.grid_mut().iter_mut() {
-        *tile = Tile::Ground;
-    }
+    // scanning each row from the left, a tile is enclosed if the loop has been crossed an odd
+    // number of times, counting only the loop tiles that connect upwards (`|`, `L` and `J`),
+    // so that `L-7` is one crossing and `L-J` is none
+    let mut enclosed = HashSet::new();
 
-    // then convert every pipe in the loop back
-    for (coord, tile) in cs.into_iter().zip(ts) {
-        grid[coord] = tile;
-    }
-
-    // convert all inner tiles to Tile::Inner
-    // (and all outer tiles to Tile::Outer)
     for y in 0..grid.rows() {
         let mut inside = false;
-        let mut prev_dir = Direction::Up;
+
         for x in 0..grid.columns() {
-            match grid[(x, y)] {
-                Tile::Ground => {
-                    if inside {
-                        grid[(x, y)] = Tile::Inner;
-                    } else {
-                        grid[(x, y)] = Tile::Outer;
-                    }
-                }
-                Tile::Pipe(Direction::Up, Direction::Down)
-                | Tile::Pipe(Direction::Down, Direction::Up) => {
+            if on_loop.contains(&(x, y)) {
+                if let Tile::Pipe(_, Direction::Up) | Tile::Pipe(Direction::Up, _) = grid[(x, y)] {
                     inside = !inside;
                 }
-                Tile::Pipe(Direction::Up, Direction::Right)
-                | Tile::Pipe(Direction::Right, Direction::Up) => {
-                    prev_dir = Direction::Up;
-                }
-                Tile::Pipe(Direction::Down, Direction::Right)
-                | Tile::Pipe(Direction::Right, Direction::Down) => {
-                    prev_dir = Direction::Down;
-                }
-                Tile::Pipe(Direction::Up, Direction::Left)
-                | Tile::Pipe(Direction::Left, Direction::Up) => match prev_dir {
-                    Direction::Down => inside = !inside,
-                    Direction::Up => {}
-                    _ => unreachable!(),
-                },
-                Tile::Pipe(Direction::Down, Direction::Left)
-                | Tile::Pipe(Direction::Left, Direction::Down) => match prev_dir {
-                    Direction::Up => inside = !inside,
-                    Direction::Down => {}
-                    _ => unreachable!(),
-                },
-                Tile::Pipe(Direction::Left, Direction::Right)
-                | Tile::Pipe(Direction::Right, Direction::Left) => {}
-                _ => panic!(),
+            } else if inside {
+                enclosed.insert((x, y));
             }
         }
     }
 
-    // count how many Tile::Inner are in the grid
-    grid.grid()
-        .iter()
-        .filter(|&&tile| tile == Tile::Inner)
-        .count()
+    LoopAnalysis {
+        grid,
+        path,
+        enclosed,
+    }
+}
+
+impl LoopAnalysis {
+    // the loop drawn with box-drawing characters,
+    // enclosed tiles as `I` and all other tiles as `O` like in the puzzle
+    fn render(&self) -> String {
+        let on_loop: HashSet<(usize, usize)> = self.path.iter().copied().collect();
+
+        let mut result = String::new();
+
+        for y in 0..self.grid.rows() {
+            for x in 0..self.grid.columns() {
+                let c = if on_loop.contains(&(x, y)) {
+                    match self.grid[(x, y)] {
+                        Tile::Pipe(Direction::Up, Direction::Down) => '│',
+                        Tile::Pipe(Direction::Left, Direction::Right) => '─',
+                        Tile::Pipe(Direction::Left, Direction::Down) => '┐',
+                        Tile::Pipe(Direction::Left, Direction::Up) => '┘',
+                        Tile::Pipe(Direction::Right, Direction::Down) => '┌',
+                        Tile::Pipe(Direction::Right, Direction::Up) => '└',
+                        tile => panic!("unexpected tile on the loop: {:?}", tile),
+                    }
+                } else if self.enclosed.contains(&(x, y)) {
+                    'I'
+                } else {
+                    'O'
+                };
+                result.push(c);
+            }
+            result.push('\n');
+        }
+
+        result
+    }
+}
+
+fn part1(input: &str) -> usize {
+    analyze(input).path.len() / 2
+}
+
+fn part2(input: &str) -> usize {
+    let LoopAnalysis { path, .. } = analyze(input);
+
+    // the loop goes through the center of every tile on it,
+    // so the enclosed tiles are exactly the lattice points inside that polygon
+    let vertices: Vec<(isize, isize)> = path
+        .into_iter()
+        .map(|(x, y)| (x as isize, y as isize))
+        .collect();
+
+    polygon::interior_points(&vertices) as usize
 }
 
 fn main() -> Result<(), std::io::Error> {
     let mut input = String::new();
     let _ = std::io::stdin().read_to_string(&mut input)?;
 
+    if std::env::args().any(|arg| arg == "--render") {
+        print!("{}", analyze(&input).render());
+        return Ok(());
+    }
+
     println!("Part 1: {}", part1(&input));
     println!("Part 2: {}", part2(&input));
 
@@ -350,4 +359,31 @@ L7JLJL-JLJLJL--JLJ.L
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn test_enclosed() {
+        for example in [EXAMPLE, EXAMPLE2, EXAMPLE3, EXAMPLE4, EXAMPLE5] {
+            let expected = part2(example);
+            let actual = analyze(example).enclosed.len();
+
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let expected = "OOOOOOOOOOO
+O┌───────┐O
+O│┌─────┐│O
+O││OOOOO││O
+O││OOOOO││O
+O│└─┐O┌─┘│O
+O│II│O│II│O
+O└──┘O└──┘O
+OOOOOOOOOOO
+";
+        let actual = analyze(EXAMPLE3).render();
+
+        assert_eq!(expected, actual);
+    }
 }
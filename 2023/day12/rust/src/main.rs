@@ -1,6 +1,9 @@
-use std::collections::HashMap;
+use rand::prelude::*;
 use std::{fmt::Display, io::Read};
 
+mod nonogram;
+use nonogram::Nonogram;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Spring {
     Operational,
@@ -27,14 +30,20 @@ impl TryFrom<char> for Spring {
     }
 }
 
+impl Display for Spring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Spring::Operational => write!(f, "."),
+            Spring::Broken => write!(f, "#"),
+            Spring::Unknown => write!(f, "?"),
+        }
+    }
+}
+
 impl Display for Line {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for spring in &self.springs {
-            match spring {
-                Spring::Operational => write!(f, ".")?,
-                Spring::Broken => write!(f, "#")?,
-                Spring::Unknown => write!(f, "?")?,
-            }
+            write!(f, "{}", spring)?;
         }
 
         write!(f, " ")?;
@@ -63,116 +72,356 @@ fn parse_line(line: &str) -> Line {
     Line { springs, records }
 }
 
-fn check(springs: &[Spring], records: &[usize]) -> bool {
-    let spring_groups: Vec<usize> = springs
-        .split(|spring| *spring == Spring::Operational)
-        .filter(|s| !s.is_empty())
-        .map(|s| s.len())
-        .collect();
+impl Line {
+    // the line repeated `factor` times, with an unknown spring in between each copy
+    fn unfold(&self, factor: usize) -> Line {
+        let mut springs = Vec::with_capacity((self.springs.len() + 1) * factor);
+        let mut records = Vec::with_capacity(self.records.len() * factor);
 
-    spring_groups == records
-}
+        for i in 0..factor {
+            if i > 0 {
+                springs.push(Spring::Unknown);
+            }
+            springs.extend_from_slice(&self.springs);
+            records.extend_from_slice(&self.records);
+        }
 
-fn check_weak(springs: &[Spring], records: &[usize]) -> Option<usize> {
-    let spring_groups: Vec<usize> = springs
-        .split(|spring| *spring == Spring::Operational)
-        .filter(|s| !s.is_empty())
-        .map(|s| s.len())
-        .collect();
+        Line { springs, records }
+    }
 
-    if spring_groups.len() <= records.len() && spring_groups == records[..spring_groups.len()] {
-        Some(spring_groups.len())
-    } else {
-        None
+    // how many springs starting at i could all be broken
+    fn broken_runs(&self) -> Vec<usize> {
+        let n = self.springs.len();
+
+        let mut run = vec![0; n + 1];
+        for i in (0..n).rev() {
+            if self.springs[i] != Spring::Operational {
+                run[i] = run[i + 1] + 1;
+            }
+        }
+
+        run
     }
-}
 
-fn combinations(
-    springs: &mut [Spring],
-    records: &[usize],
-    cache: &mut HashMap<(Vec<Spring>, Vec<usize>), usize>,
-) -> usize {
-    if let Some(r) = cache.get(&(springs.to_vec(), records.to_vec())) {
-        return *r;
+    // the possible choices for springs[i] when the next group to place is records[j],
+    // together with the (position, group index) each of them leads to
+    fn choices(&self, run: &[usize], i: usize, j: usize) -> Vec<(Spring, usize, usize)> {
+        let n = self.springs.len();
+        let mut result = Vec::with_capacity(2);
+
+        if i == n {
+            return result;
+        }
+
+        if self.springs[i] != Spring::Broken {
+            result.push((Spring::Operational, i + 1, j));
+        }
+
+        if self.springs[i] != Spring::Operational && j < self.records.len() {
+            let group = self.records[j];
+            // the group has to fit and must not be followed by another broken spring
+            if run[i] >= group && self.springs.get(i + group) != Some(&Spring::Broken) {
+                result.push((Spring::Broken, (i + group + 1).min(n), j + 1));
+            }
+        }
+
+        result
     }
 
-    if records.is_empty() {
-        return springs.iter().all(|&spring| spring != Spring::Broken) as usize;
+    // writes the springs decided by `choice` at position i
+    fn apply(&self, i: usize, j: usize, choice: Spring, springs: &mut Vec<Spring>) {
+        match choice {
+            Spring::Broken => {
+                let group = self.records[j];
+                springs.extend(std::iter::repeat_n(Spring::Broken, group));
+                if i + group < self.springs.len() {
+                    springs.push(Spring::Operational);
+                }
+            }
+            _ => springs.push(Spring::Operational),
+        }
     }
 
-    let first_unknown = match springs
-        .iter()
-        .enumerate()
-        .find(|&(_, &spring)| spring == Spring::Unknown)
-    {
-        Some((i, _)) => i,
-        None => return check(springs, records) as usize,
-    };
+    // table[i][j] = number of arrangements of springs[i..] matching records[j..]
+    //
+    // has one more row and column than there are springs and records,
+    // standing for the empty rest of the line
+    fn count_table(&self) -> Vec<Vec<u128>> {
+        let n = self.springs.len();
+        let m = self.records.len();
+        let run = self.broken_runs();
+
+        let mut table = vec![vec![0; m + 1]; n + 1];
+        table[n][m] = 1;
+
+        for i in (0..n).rev() {
+            for j in 0..=m {
+                table[i][j] = self
+                    .choices(&run, i, j)
+                    .into_iter()
+                    .map(|(_, ni, nj)| table[ni][nj])
+                    .sum();
+            }
+        }
 
-    let r1 = if !(springs[..first_unknown]
-        .iter()
-        .all(|spring| *spring == Spring::Broken)
-        && records[0] < first_unknown)
-    {
-        springs[first_unknown] = Spring::Broken;
-        let r1 = combinations(springs, records, cache);
+        table
+    }
 
-        springs[first_unknown] = Spring::Unknown;
+    fn arrangements(&self) -> u128 {
+        self.count_table()[0][0]
+    }
 
-        cache.insert((springs.to_vec(), records.to_vec()), r1);
-        r1
-    } else {
-        0
-    };
+    // every spring that has the same value in all arrangements, the others as unknown,
+    // or None if there are no arrangements at all
+    //
+    // walks the same choices as `count_table` forwards from the start of the line,
+    // only following those that still lead to at least one arrangement
+    fn determined(&self) -> Option<Vec<Spring>> {
+        let n = self.springs.len();
+        let m = self.records.len();
+        let run = self.broken_runs();
+        let table = self.count_table();
+
+        if table[0][0] == 0 {
+            return None;
+        }
 
-    match check_weak(&springs[..first_unknown], records) {
-        Some(i) => {
-            let r2 = combinations(&mut springs[first_unknown + 1..], &records[i..], cache);
-            cache.insert((springs.to_vec(), records.to_vec()), r1 + r2);
-            r1 + r2
+        let mut reachable = vec![vec![false; m + 1]; n + 1];
+        reachable[0][0] = true;
+        // whether some arrangement has spring i broken, or operational
+        let mut broken = vec![false; n];
+        let mut operational = vec![false; n];
+
+        for i in 0..n {
+            for j in 0..=m {
+                if !reachable[i][j] {
+                    continue;
+                }
+
+                for (choice, ni, nj) in self.choices(&run, i, j) {
+                    if table[ni][nj] == 0 {
+                        continue;
+                    }
+
+                    reachable[ni][nj] = true;
+                    if choice == Spring::Broken {
+                        let group = self.records[j];
+                        broken[i..i + group].fill(true);
+                        if i + group < n {
+                            operational[i + group] = true;
+                        }
+                    } else {
+                        operational[i] = true;
+                    }
+                }
+            }
         }
-        None => {
-            cache.insert((springs.to_vec(), records.to_vec()), r1);
-            r1
+
+        let springs = broken
+            .into_iter()
+            .zip(operational)
+            .map(|possible| match possible {
+                (true, false) => Spring::Broken,
+                (false, true) => Spring::Operational,
+                _ => Spring::Unknown,
+            })
+            .collect();
+
+        Some(springs)
+    }
+
+    // every valid assignment of the unknown springs, lazily
+    fn iter_arrangements(&self) -> Arrangements<'_> {
+        Arrangements::new(self)
+    }
+
+    fn sampler(&self) -> Sampler<'_> {
+        Sampler {
+            line: self,
+            run: self.broken_runs(),
+            table: self.count_table(),
         }
     }
+
+    // the same records with the given springs
+    fn with_springs(&self, springs: Vec<Spring>) -> Line {
+        Line {
+            springs,
+            records: self.records.clone(),
+        }
+    }
+}
+
+// depth first search over the choices,
+// only following those that still lead to at least one arrangement
+struct Arrangements<'a> {
+    line: &'a Line,
+    run: Vec<usize>,
+    table: Vec<Vec<u128>>,
+    current: Vec<Spring>,
+    // (position, group index, length of `current` at that point, choice to make)
+    stack: Vec<(usize, usize, usize, Spring)>,
+    // the empty line has exactly one (empty) arrangement if it has no records
+    empty: bool,
 }
 
-fn extend(springs: &mut Vec<Spring>, records: &mut Vec<usize>, n: usize) {
-    let clone_springs = springs.clone();
-    let clone_records = records.clone();
-    for _ in 0..n - 1 {
-        springs.push(Spring::Unknown);
-        springs.extend(clone_springs.clone());
-        records.extend(clone_records.clone());
+impl<'a> Arrangements<'a> {
+    fn new(line: &'a Line) -> Self {
+        let mut result = Self {
+            line,
+            run: line.broken_runs(),
+            table: line.count_table(),
+            current: Vec::with_capacity(line.springs.len()),
+            stack: Vec::new(),
+            empty: line.springs.is_empty() && line.records.is_empty(),
+        };
+        result.push_choices(0, 0);
+        result
+    }
+
+    fn push_choices(&mut self, i: usize, j: usize) {
+        // broken springs end up on top of the stack and are tried first,
+        // which yields the arrangements in the same order as the puzzle description
+        for (choice, ni, nj) in self.line.choices(&self.run, i, j) {
+            if self.table[ni][nj] > 0 {
+                self.stack.push((i, j, self.current.len(), choice));
+            }
+        }
+    }
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<Spring>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.empty {
+            self.empty = false;
+            return Some(Vec::new());
+        }
+
+        let n = self.line.springs.len();
+        let m = self.line.records.len();
+
+        while let Some((i, j, len, choice)) = self.stack.pop() {
+            self.current.truncate(len);
+            self.line.apply(i, j, choice, &mut self.current);
+
+            let (ni, nj) = (self.current.len(), j + (choice == Spring::Broken) as usize);
+            if (ni, nj) == (n, m) {
+                return Some(self.current.clone());
+            }
+
+            self.push_choices(ni, nj);
+        }
+
+        None
     }
 }
 
-fn part1(input: &str) -> usize {
-    let mut lines: Vec<Line> = input.trim().lines().map(parse_line).collect();
+// picks each of the arrangements with the same probability,
+// by weighting every choice with the number of arrangements it leads to
+struct Sampler<'a> {
+    line: &'a Line,
+    run: Vec<usize>,
+    table: Vec<Vec<u128>>,
+}
+
+impl Sampler<'_> {
+    fn sample<R: Rng>(&self, rng: &mut R) -> Option<Vec<Spring>> {
+        let n = self.line.springs.len();
+        let m = self.line.records.len();
+
+        if self.table[0][0] == 0 {
+            return None;
+        }
+
+        let mut result = Vec::with_capacity(n);
+        let (mut i, mut j) = (0, 0);
+
+        while (i, j) != (n, m) {
+            let mut pick = rng.gen_range(0..self.table[i][j]);
+
+            for (choice, ni, nj) in self.line.choices(&self.run, i, j) {
+                let count = self.table[ni][nj];
+                if pick < count {
+                    self.line.apply(i, j, choice, &mut result);
+                    (i, j) = (ni, nj);
+                    break;
+                }
+                pick -= count;
+            }
+        }
 
-    return lines.iter_mut().fold(0, |acc, line| {
-        let mut cache = HashMap::new();
-        acc + combinations(&mut line.springs, &line.records, &mut cache)
-    });
+        Some(result)
+    }
 }
 
-fn part2(input: &str) -> usize {
-    let mut lines: Vec<Line> = input.trim().lines().map(parse_line).collect();
-    lines
-        .iter_mut()
-        .for_each(|l| extend(&mut l.springs, &mut l.records, 5));
+fn part1(input: &str) -> u128 {
+    input
+        .trim()
+        .lines()
+        .map(|line| parse_line(line).arrangements())
+        .sum()
+}
 
-    return lines.iter_mut().fold(0, |acc, line| {
-        let mut cache = HashMap::new();
-        acc + combinations(&mut line.springs, &line.records, &mut cache)
-    });
+fn part2(input: &str) -> u128 {
+    input
+        .trim()
+        .lines()
+        .map(|line| parse_line(line).unfold(5).arrangements())
+        .sum()
 }
 
 fn main() -> Result<(), std::io::Error> {
     let mut input = String::new();
     let _ = std::io::stdin().read_to_string(&mut input)?;
 
+    // `cargo run -- --arrangements < input.txt`
+    // `cargo run -- --sample 3 < input.txt`
+    let args: Vec<String> = std::env::args().collect();
+    if args.iter().any(|arg| arg == "--arrangements") {
+        for line in input.trim().lines().map(parse_line) {
+            println!("{}", line);
+            for springs in line.iter_arrangements() {
+                println!("    {}", line.with_springs(springs));
+            }
+        }
+        return Ok(());
+    }
+
+    // `cargo run -- --nonogram < clues.txt`, see `Nonogram::try_from` for the format
+    if args.iter().any(|arg| arg == "--nonogram") {
+        let nonogram = Nonogram::try_from(input.as_str()).expect("invalid clues");
+        match nonogram.solve() {
+            Some(grid) => print!("{}", grid),
+            None => println!("no solution"),
+        }
+        return Ok(());
+    }
+
+    if let Some(i) = args.iter().position(|arg| arg == "--sample") {
+        let samples = args.get(i + 1).map(String::as_str).unwrap_or_default();
+        let samples: usize = samples.parse().map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("--sample expects a number of samples, got `{}`", samples),
+            )
+        })?;
+        let mut rng = rand::thread_rng();
+
+        for line in input.trim().lines().map(parse_line) {
+            println!("{}", line);
+            let sampler = line.sampler();
+            for _ in 0..samples {
+                match sampler.sample(&mut rng) {
+                    Some(springs) => println!("    {}", line.with_springs(springs)),
+                    None => println!("    no arrangements"),
+                }
+            }
+        }
+        return Ok(());
+    }
+
     println!("Part 1: {}", part1(&input));
     println!("Part 2: {}", part2(&input));
 
@@ -214,4 +463,168 @@ mod tests {
 
         assert_eq!(expected, result);
     }
+
+    #[test]
+    fn test_unfold() {
+        let line = parse_line(".# 1");
+
+        assert_eq!(line.unfold(3).to_string(), ".#?.#?.# 1,1,1");
+    }
+
+    #[test]
+    fn test_arrangements_unfolded() {
+        let line = parse_line(".??..??...?##. 1,1,3");
+
+        for factor in 1..=40 {
+            let expected = 4 * 8u128.pow(factor - 1);
+            let actual = line.unfold(factor as usize).arrangements();
+
+            assert_eq!(expected, actual);
+        }
+    }
+
+    const EXAMPLE: &str = "???.### 1,1,3
+.??..??...?##. 1,1,3
+?#?#?#?#?#?#?#? 1,3,1,6
+????.#...#... 4,1,1
+????.######..#####. 1,6,5
+?###???????? 3,2,1
+";
+
+    fn is_valid(line: &Line, springs: &[Spring]) -> bool {
+        let groups: Vec<usize> = springs
+            .split(|&s| s == Spring::Operational)
+            .filter(|g| !g.is_empty())
+            .map(|g| g.len())
+            .collect();
+
+        springs.len() == line.springs.len()
+            && groups == line.records
+            && line.springs.iter().zip(springs).all(|(&known, &s)| {
+                s != Spring::Unknown && (known == Spring::Unknown || known == s)
+            })
+    }
+
+    #[test]
+    fn test_iter_arrangements() {
+        for line in EXAMPLE.lines().map(parse_line) {
+            let arrangements: Vec<Vec<Spring>> = line.iter_arrangements().collect();
+
+            assert_eq!(arrangements.len() as u128, line.arrangements());
+            for (i, a) in arrangements.iter().enumerate() {
+                assert!(is_valid(&line, a), "{}", line.with_springs(a.clone()));
+                assert!(!arrangements[i + 1..].contains(a));
+            }
+        }
+
+        let line = parse_line("?###???????? 3,2,1");
+        let first = line.iter_arrangements().next().unwrap();
+        assert_eq!(line.with_springs(first).to_string(), ".###.##.#... 3,2,1");
+
+        assert_eq!(parse_line("# 2").iter_arrangements().count(), 0);
+    }
+
+    #[test]
+    fn test_determined() {
+        for line in EXAMPLE.lines().map(parse_line) {
+            let arrangements: Vec<Vec<Spring>> = line.iter_arrangements().collect();
+
+            let expected: Vec<Spring> = (0..line.springs.len())
+                .map(|i| {
+                    let first = arrangements[0][i];
+                    if arrangements.iter().all(|a| a[i] == first) {
+                        first
+                    } else {
+                        Spring::Unknown
+                    }
+                })
+                .collect();
+
+            assert_eq!(line.determined(), Some(expected));
+        }
+
+        assert_eq!(parse_line("# 2").determined(), None);
+    }
+
+    #[test]
+    fn test_sampler() {
+        let line = parse_line("?###???????? 3,2,1");
+        let sampler = line.sampler();
+        let mut rng = StdRng::seed_from_u64(12);
+
+        let arrangements: Vec<Vec<Spring>> = line.iter_arrangements().collect();
+        let mut seen = vec![0; arrangements.len()];
+
+        for _ in 0..1000 {
+            let sample = sampler.sample(&mut rng).unwrap();
+            let i = arrangements.iter().position(|a| *a == sample).unwrap();
+            seen[i] += 1;
+        }
+
+        // 100 expected each
+        assert!(seen.iter().all(|&count| count > 50), "{:?}", seen);
+
+        assert_eq!(parse_line("# 2").sampler().sample(&mut rng), None);
+    }
+
+    fn check_nonogram(nonogram: &str, solution: &str) {
+        let nonogram = Nonogram::try_from(nonogram).unwrap();
+        let grid = nonogram.solve().unwrap();
+
+        assert_eq!(solution, grid.to_string());
+    }
+
+    #[test]
+    fn test_nonogram() {
+        // solvable by line propagation alone
+        let clues = "1,1
+5
+5
+3
+1
+
+2
+4
+4
+4
+2
+";
+        let solution = ".#.#.
+#####
+#####
+.###.
+..#..
+";
+        check_nonogram(clues, solution);
+    }
+
+    #[test]
+    fn test_nonogram_backtracking() {
+        // both diagonals are valid, line propagation can't decide anything
+        let clues = "1
+1
+
+1
+1
+";
+        let solution = "#.
+.#
+";
+        check_nonogram(clues, solution);
+
+        let clues = "0
+2
+
+1
+1
+0
+";
+        let solution = "...
+##.
+";
+        check_nonogram(clues, solution);
+
+        let unsolvable = Nonogram::try_from("2\n\n1\n0\n").unwrap();
+        assert_eq!(unsolvable.solve(), None);
+    }
 }
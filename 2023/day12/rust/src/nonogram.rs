@@ -0,0 +1,126 @@
+use utils::grid::Grid;
+
+use super::{Line, Spring};
+
+// every row and every column is a `Line` of its own,
+// with the clues being the records of broken (filled) cells
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nonogram {
+    rows: Vec<Vec<usize>>,
+    columns: Vec<Vec<usize>>,
+}
+
+impl TryFrom<&str> for Nonogram {
+    type Error = ();
+
+    // the row clues, an empty line, then the column clues, one line each,
+    // e.g. `1,3` for a row with a group of 1 followed by a group of 3
+    // and `0` for an empty row
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        let (rows, columns) = input.trim().split_once("\n\n").ok_or(())?;
+
+        let parse_clues = |block: &str| -> Result<Vec<Vec<usize>>, ()> {
+            block
+                .lines()
+                .map(|line| {
+                    line.trim()
+                        .split(',')
+                        .map(|n| n.trim().parse::<usize>().map_err(|_| ()))
+                        .filter(|n| *n != Ok(0))
+                        .collect()
+                })
+                .collect()
+        };
+
+        Ok(Nonogram::new(parse_clues(rows)?, parse_clues(columns)?))
+    }
+}
+
+impl Nonogram {
+    pub fn new(rows: Vec<Vec<usize>>, columns: Vec<Vec<usize>>) -> Self {
+        Self { rows, columns }
+    }
+
+    pub fn solve(&self) -> Option<Grid<Spring>> {
+        let (rows, columns) = (self.rows.len(), self.columns.len());
+        let grid = Grid::new(vec![Spring::Unknown; rows * columns], rows, columns);
+
+        self.backtrack(grid)
+    }
+
+    fn backtrack(&self, mut grid: Grid<Spring>) -> Option<Grid<Spring>> {
+        if !self.propagate(&mut grid) {
+            return None;
+        }
+
+        // propagation only leaves lines that still have arrangements,
+        // so once nothing is unknown anymore every line matches its clues
+        let Some(i) = grid.grid().iter().position(|&s| s == Spring::Unknown) else {
+            return Some(grid);
+        };
+
+        for guess in [Spring::Broken, Spring::Operational] {
+            let mut next = grid.clone();
+            next.grid_mut()[i] = guess;
+
+            if let Some(solution) = self.backtrack(next) {
+                return Some(solution);
+            }
+        }
+
+        None
+    }
+
+    // fixes every cell that has the same value in all arrangements of its row or column,
+    // until nothing changes anymore
+    //
+    // returns false if some line has no arrangements at all
+    fn propagate(&self, grid: &mut Grid<Spring>) -> bool {
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for (y, records) in self.rows.iter().enumerate() {
+                let cells: Vec<(usize, usize)> = (0..grid.columns()).map(|x| (x, y)).collect();
+                match solve_line(grid, &cells, records) {
+                    Some(c) => changed |= c,
+                    None => return false,
+                }
+            }
+
+            for (x, records) in self.columns.iter().enumerate() {
+                let cells: Vec<(usize, usize)> = (0..grid.rows()).map(|y| (x, y)).collect();
+                match solve_line(grid, &cells, records) {
+                    Some(c) => changed |= c,
+                    None => return false,
+                }
+            }
+        }
+
+        true
+    }
+}
+
+// returns whether any cell was fixed, or None if the line has no arrangements
+fn solve_line(
+    grid: &mut Grid<Spring>,
+    cells: &[(usize, usize)],
+    records: &[usize],
+) -> Option<bool> {
+    let line = Line {
+        springs: cells.iter().map(|&c| grid[c]).collect(),
+        records: records.to_vec(),
+    };
+
+    let mut changed = false;
+
+    for (&cell, spring) in cells.iter().zip(line.determined()?) {
+        if grid[cell] != spring {
+            grid[cell] = spring;
+            changed = true;
+        }
+    }
+
+    Some(changed)
+}
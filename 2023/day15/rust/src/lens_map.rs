@@ -0,0 +1,122 @@
+use std::borrow::Borrow;
+use std::fmt::Display;
+
+// the Holiday ASCII String Helper algorithm
+pub fn hash(s: &str) -> u8 {
+    let mut result: u8 = 0;
+    for c in s.chars() {
+        result = result.wrapping_add(u8::try_from(c).unwrap());
+        result = result.wrapping_mul(17);
+    }
+    result
+}
+
+// the keys a `LensMap` can use, i.e. everything that can be hashed like a label
+pub trait HolidayHash {
+    fn holiday_hash(&self) -> u8;
+}
+
+impl HolidayHash for str {
+    fn holiday_hash(&self) -> u8 {
+        hash(self)
+    }
+}
+
+impl HolidayHash for &str {
+    fn holiday_hash(&self) -> u8 {
+        (**self).holiday_hash()
+    }
+}
+
+impl HolidayHash for String {
+    fn holiday_hash(&self) -> u8 {
+        self.as_str().holiday_hash()
+    }
+}
+
+// the Holiday ASCII String Helper Manual Arrangement Procedure
+//
+// 256 boxes, each keeping its entries in insertion order,
+// replacing a value keeps the position of its entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LensMap<K, V> {
+    boxes: [Vec<(K, V)>; 256],
+}
+
+impl<K, V> Default for LensMap<K, V> {
+    fn default() -> Self {
+        Self {
+            boxes: [const { Vec::new() }; 256],
+        }
+    }
+}
+
+impl<K: HolidayHash + Eq, V> LensMap<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // returns the previous value if the key was already present
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let b = &mut self.boxes[usize::from(key.holiday_hash())];
+
+        match b.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => Some(std::mem::replace(v, value)),
+            None => {
+                b.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: HolidayHash + Eq + ?Sized,
+    {
+        let b = &mut self.boxes[usize::from(key.holiday_hash())];
+
+        let i = b.iter().position(|(k, _)| k.borrow() == key)?;
+        Some(b.remove(i).1)
+    }
+
+    // the entries of the box with the given number, in order
+    pub fn get_box(&self, i: u8) -> &[(K, V)] {
+        &self.boxes[usize::from(i)]
+    }
+
+    // the sum of (box number + 1) * (slot number + 1) * focal length over all entries
+    pub fn focusing_power(&self) -> usize
+    where
+        V: Copy + Into<usize>,
+    {
+        (0..=255)
+            .map(|i| {
+                self.get_box(i)
+                    .iter()
+                    .enumerate()
+                    .map(|(j, &(_, v))| (usize::from(i) + 1) * (j + 1) * v.into())
+                    .sum::<usize>()
+            })
+            .sum()
+    }
+}
+
+// the non-empty boxes in the puzzle's format, one per line, e.g. `Box 3: [ot 9] [ab 5]`
+impl<K: Display, V: Display> Display for LensMap<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, b) in self.boxes.iter().enumerate() {
+            if b.is_empty() {
+                continue;
+            }
+
+            write!(f, "Box {}:", i)?;
+            for (k, v) in b {
+                write!(f, " [{} {}]", k, v)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
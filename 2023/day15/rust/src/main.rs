@@ -1,18 +1,8 @@
 use std::io::Read;
 
-struct BoxEntry<'a> {
-    label: &'a str,
-    focal_length: usize,
-}
+mod lens_map;
 
-fn hash(s: &str) -> u8 {
-    let mut result: u8 = 0;
-    for c in s.chars() {
-        result = result.wrapping_add(u8::try_from(c).unwrap());
-        result = result.wrapping_mul(17);
-    }
-    result
-}
+use lens_map::{hash, LensMap};
 
 fn part1(input: &str) -> usize {
     let sequence = input.trim().split(',');
@@ -20,52 +10,51 @@ fn part1(input: &str) -> usize {
     sequence.map(hash).map(usize::from).sum()
 }
 
-fn part2(input: &str) -> usize {
+// calls `observe` with every step and the boxes right after it
+fn arrange<'a>(
+    input: &'a str,
+    mut observe: impl FnMut(&'a str, &LensMap<&'a str, usize>),
+) -> LensMap<&'a str, usize> {
     let sequence = input.trim().split(',');
 
-    // TODO replace with `[const { vec![] }; 256]` once inline const is stable
-    const V: Vec<BoxEntry> = vec![];
-    let mut map = [V; 256];
+    let mut map: LensMap<&str, usize> = LensMap::new();
 
     for s in sequence {
-        if let Some(i) = s.find('-') {
-            let label = &s[..i];
-            let index = usize::from(hash(label));
-
-            if let Some(j) = map[index].iter().position(|e| e.label == label) {
-                map[index].remove(j);
-            }
-        } else if let Some(i) = s.find('=') {
-            let label = &s[..i];
-            let val = s[i + 1..].parse::<usize>().unwrap();
-            let index = usize::from(hash(label));
-
-            if let Some(j) = map[index].iter().position(|e| e.label == label) {
-                map[index][j].focal_length = val;
-            } else {
-                map[index].push(BoxEntry {
-                    label,
-                    focal_length: val,
-                })
-            }
+        if let Some(label) = s.strip_suffix('-') {
+            map.remove(label);
+        } else if let Some((label, val)) = s.split_once('=') {
+            map.insert(label, val.parse::<usize>().unwrap());
         }
+        observe(s, &map);
     }
 
-    map.iter()
-        .enumerate()
-        .map(|(i, b)| {
-            b.iter()
-                .enumerate()
-                .map(|(j, e)| (i + 1) * (j + 1) * e.focal_length)
-                .sum::<usize>()
-        })
-        .sum()
+    map
+}
+
+fn part2(input: &str) -> usize {
+    arrange(input, |_, _| {}).focusing_power()
+}
+
+// the boxes after every step, formatted like the worked example of the puzzle
+fn trace(input: &str) -> String {
+    let mut result = String::new();
+
+    arrange(input, |step, map| {
+        result.push_str(&format!("After \"{}\":\n{}\n", step, map));
+    });
+
+    result
 }
 
 fn main() -> Result<(), std::io::Error> {
     let mut input = String::new();
     let _ = std::io::stdin().read_to_string(&mut input)?;
 
+    if std::env::args().any(|arg| arg == "--trace") {
+        print!("{}", trace(&input));
+        return Ok(());
+    }
+
     println!("Part 1: {}", part1(&input));
     println!("Part 2: {}", part2(&input));
 
@@ -75,6 +64,8 @@ fn main() -> Result<(), std::io::Error> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use indexmap::IndexMap;
+    use proptest::prelude::*;
 
     const EXAMPLE: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
 
@@ -101,4 +92,122 @@ mod tests {
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn test_trace() {
+        let expected = r#"After "rn=1":
+Box 0: [rn 1]
+
+After "cm-":
+Box 0: [rn 1]
+
+After "qp=3":
+Box 0: [rn 1]
+Box 1: [qp 3]
+
+After "cm=2":
+Box 0: [rn 1] [cm 2]
+Box 1: [qp 3]
+
+After "qp-":
+Box 0: [rn 1] [cm 2]
+
+After "pc=4":
+Box 0: [rn 1] [cm 2]
+Box 3: [pc 4]
+
+After "ot=9":
+Box 0: [rn 1] [cm 2]
+Box 3: [pc 4] [ot 9]
+
+After "ab=5":
+Box 0: [rn 1] [cm 2]
+Box 3: [pc 4] [ot 9] [ab 5]
+
+After "pc-":
+Box 0: [rn 1] [cm 2]
+Box 3: [ot 9] [ab 5]
+
+After "pc=6":
+Box 0: [rn 1] [cm 2]
+Box 3: [ot 9] [ab 5] [pc 6]
+
+After "ot=7":
+Box 0: [rn 1] [cm 2]
+Box 3: [ot 7] [ab 5] [pc 6]
+
+"#;
+        let actual = trace(EXAMPLE);
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_lens_map() {
+        let mut map = LensMap::new();
+
+        assert_eq!(None, map.insert("rn", 1usize));
+        assert_eq!(None, map.insert("cm", 2));
+        assert_eq!(Some(1), map.insert("rn", 3));
+        assert_eq!(None, map.remove("qp"));
+        assert_eq!(Some(3), map.remove("rn"));
+
+        assert_eq!([("cm", 2)], map.get_box(hash("cm")));
+        assert!((0..=255).all(|i| i == hash("cm") || map.get_box(i).is_empty()));
+    }
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Insert(String, usize),
+        Remove(String),
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        // few short labels, so that replacing and removing happen often,
+        // and some of them collide, e.g. `rn` and `cm` both go into box 0
+        let label = prop_oneof!["[a-d]{1,2}", Just("rn".to_string()), Just("cm".to_string())];
+
+        prop_oneof![
+            (label.clone(), 1..=9usize).prop_map(|(l, v)| Op::Insert(l, v)),
+            label.prop_map(Op::Remove),
+        ]
+    }
+
+    proptest! {
+        // an `IndexMap` keeps every entry in insertion order, so filtering it by box
+        // gives the expected contents of that box
+        #[test]
+        fn test_lens_map_model(ops in prop::collection::vec(op(), 0..64)) {
+            let mut map = LensMap::new();
+            let mut model: IndexMap<String, usize> = IndexMap::new();
+
+            for op in ops {
+                match op {
+                    Op::Insert(label, value) => {
+                        prop_assert_eq!(model.insert(label.clone(), value), map.insert(label, value));
+                    }
+                    Op::Remove(label) => {
+                        prop_assert_eq!(model.shift_remove(&label), map.remove(label.as_str()));
+                    }
+                }
+            }
+
+            let mut expected_power = 0;
+            for i in 0..=255u8 {
+                let expected: Vec<(&String, &usize)> =
+                    model.iter().filter(|(k, _)| hash(k) == i).collect();
+                let actual: Vec<(&String, &usize)> =
+                    map.get_box(i).iter().map(|(k, v)| (k, v)).collect();
+                prop_assert_eq!(&expected, &actual);
+
+                expected_power += expected
+                    .iter()
+                    .enumerate()
+                    .map(|(j, (_, &v))| (usize::from(i) + 1) * (j + 1) * v)
+                    .sum::<usize>();
+            }
+
+            prop_assert_eq!(expected_power, map.focusing_power());
+        }
+    }
 }
@@ -34,7 +34,7 @@ impl Ord for State {
 
 impl PartialOrd for State {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(&other))
+        Some(self.cmp(other))
     }
 }
 
@@ -50,19 +50,14 @@ fn neighbours(
 
     match dir {
         Direction::Vertical => {
+            let row = &grid[y];
             for i in min..=max {
                 if x >= i {
-                    let mut cost = 0;
-                    for j in 1..=i {
-                        cost += grid[y][x - j];
-                    }
+                    let cost = row[x - i..x].iter().sum();
                     result.push((x - i, y, Direction::Horizontal, cost));
                 }
                 if x + i < grid.columns() {
-                    let mut cost = 0;
-                    for j in 1..=i {
-                        cost += grid[y][x + j];
-                    }
+                    let cost = row[x + 1..=x + i].iter().sum();
                     result.push((x + i, y, Direction::Horizontal, cost));
                 }
             }
@@ -70,17 +65,11 @@ fn neighbours(
         Direction::Horizontal => {
             for i in min..=max {
                 if y >= i {
-                    let mut cost = 0;
-                    for j in 1..=i {
-                        cost += grid[y - j][x];
-                    }
+                    let cost = grid.column(x).unwrap().skip(y - i).take(i).sum();
                     result.push((x, y - i, Direction::Vertical, cost));
                 }
                 if y + i < grid.rows() {
-                    let mut cost = 0;
-                    for j in 1..=i {
-                        cost += grid[y + j][x];
-                    }
+                    let cost = grid.column(x).unwrap().skip(y + 1).take(i).sum();
                     result.push((x, y + i, Direction::Vertical, cost));
                 }
             }
@@ -107,7 +96,7 @@ fn djikstra(grid: &Grid, min: usize, max: usize) -> usize {
 
     let neighbours_local =
         |x: usize, y: usize, dir: Direction| -> Vec<(usize, usize, Direction, usize)> {
-            neighbours(x, y, dir, min, max, &grid)
+            neighbours(x, y, dir, min, max, grid)
         };
 
     for (x, y, dir, cost) in neighbours_local(0, 0, Direction::Vertical) {
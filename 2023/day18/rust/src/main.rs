@@ -1,14 +1,16 @@
-use std::fmt::Debug;
+use std::fmt::Display;
 use std::io::{Error, Read};
+use utils::geom::polygon;
+use utils::grid;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Tile {
     Empty,
     Digged,
     Inside,
 }
 
-impl Debug for Tile {
+impl Display for Tile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Tile::Empty => write!(f, "."),
@@ -25,6 +27,7 @@ impl TryFrom<char> for Tile {
         match value {
             '.' => Ok(Tile::Empty),
             '#' => Ok(Tile::Digged),
+            '$' => Ok(Tile::Inside),
             _ => Err(()),
         }
     }
@@ -57,11 +60,11 @@ struct Instruction {
     length: usize,
 }
 
-type Grid = Vec<Vec<Tile>>;
+type Grid = grid::Grid<Tile>;
 
 #[allow(unused)]
 fn generate_grid(instructions: &[Instruction]) -> Grid {
-    let mut grid: Grid = vec![vec![Tile::Digged]];
+    let mut grid: Vec<Vec<Tile>> = vec![vec![Tile::Digged]];
 
     let (mut x, mut y): (usize, usize) = (0, 0);
 
@@ -133,46 +136,37 @@ fn generate_grid(instructions: &[Instruction]) -> Grid {
         }
     }
 
-    grid
-}
+    // rows only reach as far right as the trench went on them
+    let rows = grid.len();
+    let columns = grid.iter().map(Vec::len).max().unwrap();
 
-// this one assumes that the top left of the grid will have something like this:
-// ....#####
-// ....#....
-// ....#....
-//
-// in particular, it assumes that the grid does **not** have something like this:
-//
-// ....##..
-// ....##..
-// ..#####.
-// ..#...#.
-#[allow(unused)]
-fn flood_fill_inside(grid: &mut Grid) {
-    let first_x = grid[0]
-        .iter()
-        .enumerate()
-        .find_map(|(i, t)| match t {
-            Tile::Digged => Some(i),
-            _ => None,
+    let tiles = grid
+        .into_iter()
+        .flat_map(|mut line| {
+            line.resize(columns, Tile::Empty);
+            line
         })
-        .unwrap();
+        .collect();
 
-    let mut work_set = vec![(first_x + 1, 1)];
+    Grid::new(tiles, rows, columns)
+}
 
-    while let Some((x, y)) = work_set.pop() {
-        grid[y][x] = Tile::Inside;
-        if grid[y - 1][x] == Tile::Empty {
-            work_set.push((x, y - 1));
-        }
-        if grid[y][x - 1] == Tile::Empty {
-            work_set.push((x - 1, y));
-        }
-        if grid[y + 1][x] == Tile::Empty {
-            work_set.push((x, y + 1));
-        }
-        if grid[y][x + 1] == Tile::Empty {
-            work_set.push((x + 1, y));
+// every region of empty tiles that does not touch the edge of the grid is enclosed by the trench
+#[allow(unused)]
+fn flood_fill_inside(grid: &mut Grid) {
+    // with an empty border around the grid, everything outside of the trench
+    // is one component, the one containing the top left corner
+    let components = grid
+        .padded(1, Tile::Empty)
+        .label_components(|&t| t == Tile::Empty);
+    let outside = components.labels[(0usize, 0usize)];
+
+    for y in 0..grid.rows() {
+        for x in 0..grid.columns() {
+            let label = components.labels[(x + 1, y + 1)];
+            if label.is_some() && label != outside {
+                grid[(x, y)] = Tile::Inside;
+            }
         }
     }
 }
@@ -223,7 +217,7 @@ fn parse_instructions_part2(input: &str) -> Vec<Instruction> {
 
 #[allow(unused)]
 fn count_inside_digged(grid: &Grid) -> usize {
-    grid.iter()
+    grid.lines()
         .map(|line| {
             line.iter()
                 .filter(|&&t| t == Tile::Inside || t == Tile::Digged)
@@ -232,81 +226,37 @@ fn count_inside_digged(grid: &Grid) -> usize {
         .sum()
 }
 
-fn generate_points(instructions: &[Instruction]) -> Vec<(isize, isize)> {
-    let mut result = Vec::new();
-
-    let (mut x, mut y) = (0isize, 0isize);
-    result.push((x, y));
-    let mut it = instructions.windows(2);
-    while let Some([instruction, next_instruction]) = it.next() {
-        match instruction.direction {
-            Direction::Left => {
-                x -= instruction.length as isize;
-                match next_instruction.direction {
-                    Direction::Up => result.push((x, y + 1)),
-                    Direction::Down => result.push((x + 1, y + 1)),
-                    _ => unreachable!(),
-                }
-            }
-            Direction::Right => {
-                x += instruction.length as isize;
-                match next_instruction.direction {
-                    Direction::Up => result.push((x, y)),
-                    Direction::Down => result.push((x + 1, y)),
-                    _ => unreachable!(),
-                }
-            }
-            Direction::Up => {
-                y -= instruction.length as isize;
-                match next_instruction.direction {
-                    Direction::Left => result.push((x, y + 1)),
-                    Direction::Right => result.push((x, y)),
-                    _ => unreachable!(),
-                }
-            }
-            Direction::Down => {
-                y += instruction.length as isize;
-                match next_instruction.direction {
-                    Direction::Left => result.push((x + 1, y + 1)),
-                    Direction::Right => result.push((x + 1, y)),
-                    _ => unreachable!(),
-                }
-            }
+impl From<Direction> for polygon::Direction {
+    fn from(value: Direction) -> Self {
+        match value {
+            Direction::Left => polygon::Direction::Left,
+            Direction::Right => polygon::Direction::Right,
+            Direction::Up => polygon::Direction::Up,
+            Direction::Down => polygon::Direction::Down,
         }
     }
-
-    return result;
 }
 
-// https://en.wikipedia.org/wiki/Shoelace_formula
-// modified trapezoid formula
-// simplified as we know we only ever have straight lines
-fn calculate_area(points: &[(isize, isize)]) -> isize {
-    let mut result = 0;
+// the trench itself is the boundary of the polygon through the centers of
+// every dug cube, so the lagoon is every lattice point inside that polygon
+// plus every lattice point on its boundary
+fn lagoon_area(instructions: &[Instruction]) -> isize {
+    let steps = || instructions.iter().map(|i| (i.direction.into(), i.length));
 
-    for i in 0..points.len() - 1 {
-        result += points[i].1 * (points[i].0 - points[i + 1].0)
-    }
-    // not needed, because the last step is always straight up or down,
-    // making its area 0
-    // result +=
-    //     (points[points.len() - 1].1 + points[0].1) * (points[points.len() - 1].0 - points[0].0);
+    let vertices = polygon::trace(steps());
+    let boundary = polygon::path_length(steps()) as isize;
 
-    result
+    polygon::interior_points(&vertices) + boundary
 }
 
 fn part1(input: &str) -> isize {
     let instructions = parse_instructions_part1(input);
-    let points = generate_points(&instructions);
-
-    calculate_area(&points)
+    lagoon_area(&instructions)
 }
 
 fn part2(input: &str) -> isize {
     let instructions = parse_instructions_part2(input);
-    let points = generate_points(&instructions);
-
-    calculate_area(&points)
+    lagoon_area(&instructions)
 }
 
 fn main() -> Result<(), Error> {
@@ -383,10 +333,9 @@ R 3 (#000000)
 D 3 (#000000)
 ";
 
-        let expected = "
-####
-#..#
-#..#
+        let expected = "####.........
+#..#.........
+#..#.........
 #..####..####
 #.....#..#..#
 #.....#..#..#
@@ -396,14 +345,63 @@ D 3 (#000000)
 #############
 ";
 
-        let expected: Vec<Vec<Tile>> = expected
-            .trim()
-            .lines()
-            .map(|line| line.chars().map(|c| c.try_into().unwrap()).collect())
-            .collect();
-
         let instructions = parse_instructions_part1(input);
         let actual = generate_grid(&instructions);
-        assert_eq!(expected, actual);
+        assert_eq!(expected, actual.to_string());
+    }
+
+    #[test]
+    fn test_flood_fill_inside() {
+        let expected = "#######
+#$$$$$#
+###$$$#
+..#$$$#
+..#$$$#
+###$###
+#$$$#..
+##$$###
+.#$$$$#
+.######
+";
+
+        let instructions = parse_instructions_part1(EXAMPLE);
+        let mut grid = generate_grid(&instructions);
+        flood_fill_inside(&mut grid);
+
+        assert_eq!(expected, grid.to_string());
+        assert_eq!(62, count_inside_digged(&grid));
+    }
+
+    #[test]
+    fn test_flood_fill_inside_2() {
+        // an L-shaped lagoon, with empty tiles outside of the trench on the top right
+        let input = "R 2 (#000000)
+D 2 (#000000)
+R 2 (#000000)
+D 2 (#000000)
+L 4 (#000000)
+U 4 (#000000)
+";
+
+        let expected = "###..
+#.#..
+#.###
+#...#
+#####
+";
+        let expected_filled = "###..
+#$#..
+#$###
+#$$$#
+#####
+";
+
+        let instructions = parse_instructions_part1(input);
+        let mut grid = generate_grid(&instructions);
+        assert_eq!(expected, grid.to_string());
+
+        flood_fill_inside(&mut grid);
+        assert_eq!(expected_filled, grid.to_string());
+        assert_eq!(part1(input) as usize, count_inside_digged(&grid));
     }
 }
@@ -0,0 +1,457 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Write};
+use std::ops::Range;
+
+use super::{Condition, Part, RuleRange, Workflow, WorkflowResult, FULL_RANGE};
+
+pub type NodeId = usize;
+
+pub const ACCEPT: NodeId = 0;
+pub const REJECT: NodeId = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Node {
+    Accept,
+    Reject,
+    Test {
+        condition: Condition,
+        pass: NodeId,
+        fail: NodeId,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompileError<'a> {
+    UnknownWorkflow(&'a str),
+    // the workflow names along the cycle, in order
+    Cycle(Vec<&'a str>),
+}
+
+impl Display for CompileError<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompileError::UnknownWorkflow(name) => write!(f, "unknown workflow `{}`", name),
+            CompileError::Cycle(names) => {
+                write!(f, "cycle between workflows {}", names.join(" -> "))
+            }
+        }
+    }
+}
+
+// rules that can never be taken, given the constraints of every path leading to them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unreachable<'a> {
+    Workflow(&'a str),
+    Rule(&'a str, usize),
+    Fallback(&'a str),
+}
+
+// a box of accepted parts and the workflows it passed through, in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Accepted<'a> {
+    pub range: RuleRange,
+    pub path: Vec<&'a str>,
+}
+
+// all workflows linked into a single DAG
+//
+// every rule becomes one `Node::Test` whose `fail` edge leads to the next rule
+// of the same workflow (or wherever the fallback points to),
+// jumps are resolved to the first rule of the target workflow
+//
+// workflows without any rules do not get nodes of their own,
+// jumps to them are resolved to wherever their fallback points to,
+// remembering the workflows passed on the way in `hops`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecisionTree<'a> {
+    nodes: Vec<Node>,
+    // workflow name and rule index of every `Node::Test`
+    origins: Vec<Option<(&'a str, usize)>>,
+    // the rule-less workflows passed along the `pass` and `fail` edge of every `Node::Test`
+    hops: Vec<[Vec<&'a str>; 2]>,
+    // the nodes belonging to each workflow, empty for workflows without rules
+    workflows: Vec<(&'a str, Range<NodeId>)>,
+    root: NodeId,
+    // the rule-less workflows passed before reaching `root`
+    root_hops: Vec<&'a str>,
+}
+
+impl<'a> DecisionTree<'a> {
+    pub fn compile(workflows: &HashMap<&'a str, Workflow<'a>>) -> Result<Self, CompileError<'a>> {
+        // sorted so that node indices (and thus the DOT output) are deterministic
+        let mut names: Vec<&'a str> = workflows.keys().copied().collect();
+        names.sort_unstable();
+
+        let mut nodes = vec![Node::Accept, Node::Reject];
+        let mut origins = vec![None, None];
+        let mut hops = vec![Default::default(), Default::default()];
+        let mut entries = HashMap::new();
+        let mut ranges = Vec::new();
+
+        for &name in &names {
+            let workflow = &workflows[name];
+            ranges.push((name, nodes.len()..nodes.len() + workflow.rules.len()));
+            if workflow.rules.is_empty() {
+                continue;
+            }
+
+            entries.insert(name, nodes.len());
+            for (i, rule) in workflow.rules.iter().enumerate() {
+                // placeholder edges, filled in once every entry is known
+                nodes.push(Node::Test {
+                    condition: rule.condition,
+                    pass: REJECT,
+                    fail: REJECT,
+                });
+                origins.push(Some((name, i)));
+                hops.push(Default::default());
+            }
+        }
+
+        // the node `result` leads to and the rule-less workflows passed on the way
+        let resolve =
+            |result: WorkflowResult<'a>| -> Result<(NodeId, Vec<&'a str>), CompileError<'a>> {
+                let mut current = result;
+                let mut seen = Vec::new();
+
+                loop {
+                    match current {
+                        WorkflowResult::Accept => return Ok((ACCEPT, seen)),
+                        WorkflowResult::Reject => return Ok((REJECT, seen)),
+                        WorkflowResult::Jump(name) => {
+                            if let Some(&entry) = entries.get(name) {
+                                return Ok((entry, seen));
+                            }
+
+                            let workflow = workflows
+                                .get(name)
+                                .ok_or(CompileError::UnknownWorkflow(name))?;
+
+                            if seen.contains(&name) {
+                                seen.push(name);
+                                return Err(CompileError::Cycle(seen));
+                            }
+                            seen.push(name);
+
+                            current = workflow.fallback;
+                        }
+                    }
+                }
+            };
+
+        for &name in &names {
+            let Some(&entry) = entries.get(name) else {
+                continue;
+            };
+
+            let workflow = &workflows[name];
+            let fallback = resolve(workflow.fallback)?;
+
+            for (i, rule) in workflow.rules.iter().enumerate() {
+                let (pass, pass_hops) = resolve(rule.result)?;
+                let (fail, fail_hops) = if i + 1 < workflow.rules.len() {
+                    (entry + i + 1, Vec::new())
+                } else {
+                    fallback.clone()
+                };
+
+                if let Node::Test {
+                    pass: p, fail: f, ..
+                } = &mut nodes[entry + i]
+                {
+                    *p = pass;
+                    *f = fail;
+                }
+                hops[entry + i] = [pass_hops, fail_hops];
+            }
+        }
+
+        let (root, root_hops) = resolve(WorkflowResult::Jump("in"))?;
+
+        let tree = Self {
+            nodes,
+            origins,
+            hops,
+            workflows: ranges,
+            root,
+            root_hops,
+        };
+
+        match tree.find_cycle() {
+            Some(cycle) => Err(CompileError::Cycle(cycle)),
+            None => Ok(tree),
+        }
+    }
+
+    pub fn accepts(&self, part: Part) -> bool {
+        let mut current = self.root;
+
+        loop {
+            match self.nodes[current] {
+                Node::Accept => return true,
+                Node::Reject => return false,
+                Node::Test {
+                    condition,
+                    pass,
+                    fail,
+                } => {
+                    current = if condition.evaluate(part) { pass } else { fail };
+                }
+            }
+        }
+    }
+
+    // every box of parts within `constraints` that ends up accepted
+    //
+    // the boxes are split by every rule they pass through,
+    // so they are pairwise disjoint
+    pub fn query(&self, constraints: RuleRange) -> Vec<Accepted<'a>> {
+        let mut result = Vec::new();
+
+        let mut stack: Vec<(NodeId, RuleRange, Vec<&'a str>)> =
+            vec![(self.root, constraints, self.root_hops.clone())];
+
+        while let Some((node, range, mut path)) = stack.pop() {
+            if range.is_empty() {
+                continue;
+            }
+
+            match self.nodes[node] {
+                Node::Accept => result.push(Accepted { range, path }),
+                Node::Reject => {}
+                Node::Test {
+                    condition,
+                    pass,
+                    fail,
+                } => {
+                    let (name, _) = self.origins[node].unwrap();
+                    if path.last() != Some(&name) {
+                        path.push(name);
+                    }
+
+                    let [pass_hops, fail_hops] = &self.hops[node];
+                    let (matched, rest) = condition.split(range);
+
+                    let mut fail_path = path.clone();
+                    fail_path.extend(fail_hops);
+                    path.extend(pass_hops);
+
+                    stack.push((fail, rest, fail_path));
+                    stack.push((pass, matched, path));
+                }
+            }
+        }
+
+        result
+    }
+
+    fn find_cycle(&self) -> Option<Vec<&'a str>> {
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        enum State {
+            New,
+            Active,
+            Done,
+        }
+
+        let mut states = vec![State::New; self.nodes.len()];
+
+        for start in 0..self.nodes.len() {
+            if states[start] != State::New {
+                continue;
+            }
+
+            // iterative DFS, `path` holds the currently active nodes
+            let mut path = vec![start];
+            let mut stack = vec![(start, 0)];
+            states[start] = State::Active;
+
+            while let Some((node, edge)) = stack.pop() {
+                let successors = match self.nodes[node] {
+                    Node::Test { pass, fail, .. } => vec![pass, fail],
+                    _ => vec![],
+                };
+
+                let Some(&next) = successors.get(edge) else {
+                    states[node] = State::Done;
+                    path.pop();
+                    continue;
+                };
+
+                stack.push((node, edge + 1));
+
+                match states[next] {
+                    State::New => {
+                        states[next] = State::Active;
+                        path.push(next);
+                        stack.push((next, 0));
+                    }
+                    State::Active => {
+                        let i = path.iter().position(|&n| n == next).unwrap();
+
+                        let mut cycle: Vec<&'a str> = Vec::new();
+                        for &n in &path[i..] {
+                            let (name, _) = self.origins[n].unwrap();
+                            if cycle.last() != Some(&name) {
+                                cycle.push(name);
+                            }
+                        }
+                        cycle.push(cycle[0]);
+
+                        return Some(cycle);
+                    }
+                    State::Done => {}
+                }
+            }
+        }
+
+        None
+    }
+
+    // every node in an order where each node comes before all of its successors
+    fn topological_order(&self) -> Vec<NodeId> {
+        let mut incoming = vec![0; self.nodes.len()];
+        for node in &self.nodes {
+            if let Node::Test { pass, fail, .. } = *node {
+                incoming[pass] += 1;
+                incoming[fail] += 1;
+            }
+        }
+
+        let mut queue: VecDeque<NodeId> = (0..self.nodes.len())
+            .filter(|&node| incoming[node] == 0)
+            .collect();
+        let mut result = Vec::with_capacity(self.nodes.len());
+
+        while let Some(node) = queue.pop_front() {
+            result.push(node);
+
+            if let Node::Test { pass, fail, .. } = self.nodes[node] {
+                for next in [pass, fail] {
+                    incoming[next] -= 1;
+                    if incoming[next] == 0 {
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    // pushes the full range of parts through the tree,
+    // anything that none of them reach is unreachable
+    //
+    // nodes are visited once each in topological order, taking all boxes that reach them
+    // from any predecessor at once, so shared workflows don't multiply the work
+    pub fn unreachable(&self) -> Vec<Unreachable<'a>> {
+        let mut pass_reached = vec![false; self.nodes.len()];
+        let mut fail_reached = vec![false; self.nodes.len()];
+        let mut reached: HashSet<&'a str> = self.root_hops.iter().copied().collect();
+
+        // the boxes arriving at every node, without duplicates
+        let mut arriving: Vec<Vec<RuleRange>> = vec![Vec::new(); self.nodes.len()];
+        arriving[self.root].push(FULL_RANGE);
+
+        for node in self.topological_order() {
+            let Node::Test {
+                condition,
+                pass,
+                fail,
+            } = self.nodes[node]
+            else {
+                continue;
+            };
+
+            let boxes = std::mem::take(&mut arriving[node]);
+            if boxes.is_empty() {
+                continue;
+            }
+
+            reached.insert(self.origins[node].unwrap().0);
+            let [pass_hops, fail_hops] = &self.hops[node];
+
+            for range in boxes {
+                let (matched, rest) = condition.split(range);
+
+                if !matched.is_empty() {
+                    pass_reached[node] = true;
+                    reached.extend(pass_hops);
+                    if !arriving[pass].contains(&matched) {
+                        arriving[pass].push(matched);
+                    }
+                }
+                if !rest.is_empty() {
+                    fail_reached[node] = true;
+                    reached.extend(fail_hops);
+                    if !arriving[fail].contains(&rest) {
+                        arriving[fail].push(rest);
+                    }
+                }
+            }
+        }
+
+        let mut result = Vec::new();
+
+        for (name, nodes) in &self.workflows {
+            if !reached.contains(name) {
+                result.push(Unreachable::Workflow(name));
+                continue;
+            }
+
+            for (i, node) in nodes.clone().enumerate() {
+                if !pass_reached[node] {
+                    result.push(Unreachable::Rule(name, i));
+                }
+            }
+
+            if !nodes.is_empty() && !fail_reached[nodes.end - 1] {
+                result.push(Unreachable::Fallback(name));
+            }
+        }
+
+        result
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut result = String::new();
+
+        writeln!(result, "digraph workflows {{").unwrap();
+        writeln!(result, "    start [shape=point];").unwrap();
+        writeln!(result, "    start -> n{};", self.root).unwrap();
+
+        for (i, node) in self.nodes.iter().enumerate() {
+            match node {
+                Node::Accept => {
+                    writeln!(result, "    n{} [label=\"A\", shape=box, color=green];", i).unwrap();
+                }
+                Node::Reject => {
+                    writeln!(result, "    n{} [label=\"R\", shape=box, color=red];", i).unwrap();
+                }
+                Node::Test {
+                    condition,
+                    pass,
+                    fail,
+                } => {
+                    let (name, rule) = self.origins[i].unwrap();
+                    writeln!(
+                        result,
+                        "    n{} [label=\"{}[{}]\\n{}\"];",
+                        i, name, rule, condition
+                    )
+                    .unwrap();
+                    writeln!(result, "    n{} -> n{} [label=\"true\"];", i, pass).unwrap();
+                    writeln!(
+                        result,
+                        "    n{} -> n{} [label=\"false\", style=dashed];",
+                        i, fail
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        writeln!(result, "}}").unwrap();
+
+        result
+    }
+}
@@ -1,9 +1,13 @@
 use std::{
-    cmp::{max, min},
     collections::HashMap,
-    io::{Error, Read},
+    fmt::Display,
+    io::{Error, ErrorKind, Read},
     ops::{Index, IndexMut},
 };
+use utils::ranges::{HyperRect, Interval};
+
+mod compile;
+use compile::DecisionTree;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ConditionType {
@@ -33,6 +37,15 @@ enum Category {
 
 impl Category {
     const VALUES: [Self; 4] = [Self::Cool, Self::Musical, Self::Aerodynamic, Self::Shiny];
+
+    fn symbol(self) -> char {
+        match self {
+            Category::Cool => 'x',
+            Category::Musical => 'm',
+            Category::Aerodynamic => 'a',
+            Category::Shiny => 's',
+        }
+    }
 }
 
 impl TryFrom<char> for Category {
@@ -56,6 +69,17 @@ struct Condition {
     value: usize,
 }
 
+impl Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let category = self.category.symbol();
+        let ctype = match self.ctype {
+            ConditionType::Less => '<',
+            ConditionType::Greater => '>',
+        };
+        write!(f, "{}{}{}", category, ctype, self.value)
+    }
+}
+
 impl Condition {
     fn evaluate(&self, part: Part) -> bool {
         let v = part[self.category];
@@ -64,6 +88,34 @@ impl Condition {
             ConditionType::Greater => v > self.value,
         }
     }
+
+    // every value of `category` matching this condition
+    fn values(&self) -> Interval {
+        let value = self.value as i64;
+        match self.ctype {
+            ConditionType::Less => Interval::new(i64::MIN, value),
+            ConditionType::Greater => Interval::new(value + 1, i64::MAX),
+        }
+    }
+
+    // returns the part of the range matching this condition and the rest
+    fn split(&self, range: RuleRange) -> (RuleRange, RuleRange) {
+        let axis = self.category as usize;
+        let values = self.values();
+
+        let mut matched = range;
+        matched[axis] = range[axis].intersection(&values);
+
+        // `values` is unbounded on one side, so at most one interval is left
+        let mut rest = range;
+        rest[axis] = match range[axis].difference(&values).intervals() {
+            [] => Interval::new(0, 0),
+            [interval] => *interval,
+            _ => unreachable!(),
+        };
+
+        (matched, rest)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -172,7 +224,7 @@ impl TryFrom<&str> for Part {
     }
 }
 
-fn parse_input(input: &str) -> (HashMap<&str, Workflow>, Vec<Part>) {
+fn parse_input(input: &str) -> (HashMap<&str, Workflow<'_>>, Vec<Part>) {
     let f = input.trim().find("\n\n").unwrap();
     let workflows_input = input[..f].trim();
     let parts_input = input[f + 1..].trim();
@@ -199,134 +251,114 @@ fn parse_input(input: &str) -> (HashMap<&str, Workflow>, Vec<Part>) {
 
 fn part1(input: &str) -> usize {
     let (workflows, parts) = parse_input(input);
+    let tree = DecisionTree::compile(&workflows).unwrap();
 
-    let mut result = 0;
-
-    for part in parts {
-        let mut current = WorkflowResult::Jump("in");
-
-        'outer: loop {
-            match current {
-                WorkflowResult::Jump(name) => {
-                    let workflow = &workflows[name];
-                    for rule in &workflow.rules {
-                        if rule.condition.evaluate(part) {
-                            current = rule.result;
-                            continue 'outer;
-                        }
-                    }
-                    current = workflow.fallback;
-                }
-                WorkflowResult::Accept => {
-                    for i in 0..4 {
-                        result += part.arr[i];
-                    }
-                    break;
-                }
-                WorkflowResult::Reject => break,
-            }
-        }
-    }
-
-    result
+    parts
+        .into_iter()
+        .filter(|&part| tree.accepts(part))
+        .map(|part| part.arr.iter().sum::<usize>())
+        .sum()
 }
 
-// [range.0, range.1)
-// i.e. left is inclusive, right is exclusive
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct RuleRange {
-    arr: [(usize, usize); 4],
-}
+// one interval of allowed values per category, indexed by `Category as usize`
+type RuleRange = HyperRect<4>;
+
+const FULL_RANGE: RuleRange = HyperRect::new([Interval::inclusive(1, 4000); 4]);
 
-impl Default for RuleRange {
-    fn default() -> Self {
-        Self {
-            arr: [(1, 4001); 4],
+// comma separated constraints on top of `FULL_RANGE`,
+// either a single value or an inclusive range per category,
+// e.g. `x=1..4000,m=100`
+fn parse_constraints(s: &str) -> Result<RuleRange, ()> {
+    let mut result = FULL_RANGE;
+
+    for constraint in s.split(',') {
+        let (category, values) = constraint.trim().split_once('=').ok_or(())?;
+
+        let mut chars = category.chars();
+        let category: Category = chars.next().ok_or(())?.try_into()?;
+        if chars.next().is_some() {
+            return Err(());
         }
-    }
-}
 
-impl Index<Category> for RuleRange {
-    type Output = (usize, usize);
+        let (lo, hi) = values.split_once("..").unwrap_or((values, values));
+        let lo: i64 = lo.parse().map_err(|_| ())?;
+        let hi: i64 = hi.parse().map_err(|_| ())?;
 
-    fn index(&self, index: Category) -> &Self::Output {
-        &self.arr[index as usize]
+        let axis = category as usize;
+        result[axis] = result[axis].intersection(&Interval::inclusive(lo, hi));
     }
-}
 
-impl IndexMut<Category> for RuleRange {
-    fn index_mut(&mut self, index: Category) -> &mut Self::Output {
-        &mut self.arr[index as usize]
-    }
+    Ok(result)
 }
 
-fn evaluate_range<'a>(
-    workflow: &'a Workflow,
-    mut ranges: RuleRange,
-) -> Vec<(RuleRange, WorkflowResult<'a>)> {
-    let mut result = Vec::new();
-
-    for rule in &workflow.rules {
-        match rule.condition.ctype {
-            ConditionType::Less => {
-                let upper_bound = min(rule.condition.value, ranges[rule.condition.category].1);
-                let mut new_range = ranges;
-                new_range[rule.condition.category].1 = upper_bound;
-                result.push((new_range, rule.result));
-                ranges[rule.condition.category].0 = upper_bound;
+// the inverse of `parse_constraints`, separated by spaces
+fn format_range(range: &RuleRange) -> String {
+    Category::VALUES
+        .iter()
+        .map(|&category| {
+            let Interval { start, end } = range[category as usize];
+            if end - start == 1 {
+                format!("{}={}", category.symbol(), start)
+            } else {
+                format!("{}={}..{}", category.symbol(), start, end - 1)
             }
-            ConditionType::Greater => {
-                let lower_bound = max(rule.condition.value + 1, ranges[rule.condition.category].0);
-                let mut new_range = ranges;
-                new_range[rule.condition.category].0 = lower_bound;
-                result.push((new_range, rule.result));
-                ranges[rule.condition.category].1 = lower_bound;
-            }
-        }
-    }
-
-    result.push((ranges, workflow.fallback));
-
-    result
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 fn part2(input: &str) -> usize {
     let (workflows, _) = parse_input(input);
-
-    let mut result = 0;
-
-    let mut ranges: Vec<(RuleRange, WorkflowResult)> =
-        vec![(RuleRange::default(), WorkflowResult::Jump("in"))];
-
-    loop {
-        let mut extend = Vec::new();
-
-        if let Some((ranges, wresult)) = ranges.pop() {
-            match wresult {
-                WorkflowResult::Jump(s) => extend.extend(evaluate_range(&workflows[s], ranges)),
-                WorkflowResult::Accept => {
-                    let mut tmp = 1;
-                    for category in Category::VALUES {
-                        tmp *= ranges[category].1 - ranges[category].0;
-                    }
-                    result += tmp;
-                }
-                WorkflowResult::Reject => {}
-            }
-        } else {
-            break;
-        }
-
-        ranges.extend(extend);
-    }
-
-    result
+    let tree = DecisionTree::compile(&workflows).unwrap();
+
+    // the rules split the parts with intersections and differences,
+    // so the accepted boxes are disjoint and their volumes simply add up
+    tree.query(FULL_RANGE)
+        .iter()
+        .map(|accepted| accepted.range.volume() as usize)
+        .sum()
 }
 
 fn main() -> Result<(), Error> {
     let mut input = String::new();
     let _ = std::io::stdin().read_to_string(&mut input)?;
 
+    // `cargo run -- --dot < input.txt | dot -Tsvg > workflows.svg`
+    if std::env::args().any(|arg| arg == "--dot") {
+        let (workflows, _) = parse_input(&input);
+        let tree = DecisionTree::compile(&workflows)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+
+        for unreachable in tree.unreachable() {
+            eprintln!("unreachable: {:?}", unreachable);
+        }
+        print!("{}", tree.to_dot());
+        return Ok(());
+    }
+
+    // `cargo run -- --query x=1..4000,m=100 < input.txt`
+    let mut args = std::env::args().skip_while(|arg| arg != "--query");
+    if args.next().is_some() {
+        let (workflows, _) = parse_input(&input);
+        let tree = DecisionTree::compile(&workflows)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+
+        let usage = "usage: --query <category>=<value>[..<value>],...";
+        let constraints = args
+            .next()
+            .and_then(|arg| parse_constraints(&arg).ok())
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, usage))?;
+
+        for accepted in tree.query(constraints) {
+            println!(
+                "{} via {}",
+                format_range(&accepted.range),
+                accepted.path.join(" -> ")
+            );
+        }
+        return Ok(());
+    }
+
     println!("Part 1: {}", part1(&input));
     println!("Part 2: {}", part2(&input));
 
@@ -336,6 +368,7 @@ fn main() -> Result<(), Error> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use compile::{CompileError, Unreachable};
 
     const EXAMPLE: &str = "px{a<2006:qkq,m>2090:A,rfg}
 pv{a>1716:R,A}
@@ -371,4 +404,152 @@ hdj{m>838:A,pv}
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn test_compile_unreachable() {
+        let input = "in{x<10:ab,x<5:R,s>100:R,A}
+ab{x>20:R,m<10:A,R}
+cd{A}
+ef{x<3:A,R}
+
+{x=1,m=1,a=1,s=1}
+";
+        let (workflows, _) = parse_input(input);
+        let tree = DecisionTree::compile(&workflows).unwrap();
+
+        let mut expected = vec![
+            Unreachable::Rule("ab", 0),
+            Unreachable::Rule("in", 1),
+            Unreachable::Workflow("cd"),
+            Unreachable::Workflow("ef"),
+        ];
+        let mut actual = tree.unreachable();
+        actual.sort_by_key(|u| format!("{:?}", u));
+        expected.sort_by_key(|u| format!("{:?}", u));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_compile_unreachable_shared() {
+        // every workflow is reached along many paths, which must not be walked one by one
+        let mut input = String::from("in{x<2000:w0,w0}\n");
+        for i in 0..40 {
+            input += &format!("w{}{{m<{}:w{},w{}}}\n", i, 100 + i, i + 1, i + 1);
+        }
+        input += "w40{m<50:A,nop}\nnop{R}\nlost{A}\n\n{x=1,m=1,a=1,s=1}\n";
+
+        let (workflows, _) = parse_input(&input);
+        let tree = DecisionTree::compile(&workflows).unwrap();
+
+        assert_eq!(tree.unreachable(), vec![Unreachable::Workflow("lost")]);
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        let input = "in{x<10:ab,A}
+
+{x=1,m=1,a=1,s=1}
+";
+        let (workflows, _) = parse_input(input);
+        assert_eq!(
+            DecisionTree::compile(&workflows),
+            Err(CompileError::UnknownWorkflow("ab"))
+        );
+
+        let input = "in{x<10:ab,A}
+ab{m<10:A,cd}
+cd{s<10:R,ab}
+
+{x=1,m=1,a=1,s=1}
+";
+        let (workflows, _) = parse_input(input);
+        assert_eq!(
+            DecisionTree::compile(&workflows),
+            Err(CompileError::Cycle(vec!["ab", "cd", "ab"]))
+        );
+    }
+
+    #[test]
+    fn test_compile_dot() {
+        let (workflows, _) = parse_input(EXAMPLE);
+        let tree = DecisionTree::compile(&workflows).unwrap();
+        let dot = tree.to_dot();
+
+        assert!(dot.starts_with("digraph workflows {"));
+        assert!(dot.contains("[label=\"in[0]\\ns<1351\"]"));
+        assert_eq!(dot.matches("label=\"true\"").count(), 14);
+    }
+
+    #[test]
+    fn test_query() {
+        let (workflows, _) = parse_input(EXAMPLE);
+        let tree = DecisionTree::compile(&workflows).unwrap();
+
+        let constraints = parse_constraints("x=1..4000,m=100").unwrap();
+        let accepted = tree.query(constraints);
+
+        for (i, a) in accepted.iter().enumerate() {
+            assert_eq!(a.range[Category::Musical as usize], Interval::new(100, 101));
+            for b in &accepted[i + 1..] {
+                assert!(!a.range.intersects(&b.range));
+            }
+        }
+
+        // restricting the query is the same as restricting the unconstrained result
+        let expected: u64 = tree
+            .query(FULL_RANGE)
+            .iter()
+            .map(|a| a.range.intersection(&constraints).volume())
+            .sum();
+        let actual: u64 = accepted.iter().map(|a| a.range.volume()).sum();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_query_path() {
+        let (workflows, _) = parse_input(EXAMPLE);
+        let tree = DecisionTree::compile(&workflows).unwrap();
+
+        let constraints = parse_constraints("x=787,m=2655,a=1222,s=2876").unwrap();
+        let accepted = tree.query(constraints);
+
+        assert_eq!(accepted.len(), 1);
+        assert_eq!(accepted[0].path, vec!["in", "qqz", "qs", "lnx"]);
+        assert_eq!(
+            format_range(&accepted[0].range),
+            "x=787 m=2655 a=1222 s=2876"
+        );
+
+        let constraints = parse_constraints("x=1679,m=44,a=2067,s=496").unwrap();
+        assert!(tree.query(constraints).is_empty());
+    }
+
+    #[test]
+    fn test_query_path_rule_less() {
+        let input = "in{x<10:cd,R}
+cd{ef}
+ef{m<10:A,R}
+
+{x=1,m=1,a=1,s=1}
+";
+        let (workflows, _) = parse_input(input);
+        let tree = DecisionTree::compile(&workflows).unwrap();
+
+        let accepted = tree.query(FULL_RANGE);
+
+        assert_eq!(accepted.len(), 1);
+        assert_eq!(accepted[0].path, vec!["in", "cd", "ef"]);
+    }
+
+    #[test]
+    fn test_parse_constraints() {
+        let mut expected = FULL_RANGE;
+        expected[Category::Musical as usize] = Interval::new(100, 101);
+        expected[Category::Shiny as usize] = Interval::new(10, 21);
+
+        assert_eq!(parse_constraints("m=100, s=10..20"), Ok(expected));
+        assert_eq!(parse_constraints("q=100"), Err(()));
+        assert_eq!(parse_constraints("m=abc"), Err(()));
+    }
 }
@@ -0,0 +1,242 @@
+use num::integer::lcm;
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use super::circuit::{Circuit, Kind, ModuleId, Pulse, BROADCASTER, BUTTON};
+use super::PulseType;
+
+// One of the inputs of the terminal conjunction together with the part of the circuit it depends
+// on. It sends a high pulse to the terminal conjunction exactly every `period` button presses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubCounter {
+    pub output: ModuleId,
+    pub modules: HashSet<ModuleId>,
+    pub period: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis {
+    pub target: ModuleId,
+    pub terminal: ModuleId,
+    pub counters: Vec<SubCounter>,
+}
+
+impl Analysis {
+    // the first button press on which every sub-counter sends a high pulse,
+    // which makes the terminal conjunction send a low pulse to the target
+    pub fn presses(&self) -> usize {
+        self.counters.iter().map(|c| c.period).fold(1, lcm)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnalysisError<'a> {
+    UnknownTarget(&'a str),
+    NotSingleInput {
+        target: &'a str,
+        inputs: Vec<&'a str>,
+    },
+    NotConjunction {
+        target: &'a str,
+        module: &'a str,
+        kind: Kind,
+    },
+    Shared {
+        counters: (&'a str, &'a str),
+        module: &'a str,
+    },
+    Feedback {
+        counter: &'a str,
+        terminal: &'a str,
+    },
+    NoHighPulse {
+        counter: &'a str,
+        presses: usize,
+    },
+    NotPeriodic {
+        counter: &'a str,
+        first: usize,
+        second: usize,
+    },
+}
+
+impl Display for AnalysisError<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalysisError::UnknownTarget(target) => {
+                write!(f, "there is no module called `{}`", target)
+            }
+            AnalysisError::NotSingleInput { target, inputs } => write!(
+                f,
+                "`{}` should be fed by exactly one conjunction, but its inputs are [{}]",
+                target,
+                inputs.join(", ")
+            ),
+            AnalysisError::NotConjunction {
+                target,
+                module,
+                kind,
+            } => write!(
+                f,
+                "`{}` should be fed by a conjunction, but `{}` is a {:?}",
+                target, module, kind
+            ),
+            AnalysisError::Shared { counters, module } => write!(
+                f,
+                "`{}` and `{}` are not independent, they both depend on `{}`",
+                counters.0, counters.1, module
+            ),
+            AnalysisError::Feedback { counter, terminal } => write!(
+                f,
+                "`{}` is not independent, it depends on the output of `{}`",
+                counter, terminal
+            ),
+            AnalysisError::NoHighPulse { counter, presses } => write!(
+                f,
+                "`{}` did not send two high pulses within {} button presses",
+                counter, presses
+            ),
+            AnalysisError::NotPeriodic {
+                counter,
+                first,
+                second,
+            } => write!(
+                f,
+                "`{}` is not a counter, it first sent a high pulse on press {} and then on press {}",
+                counter, first, second
+            ),
+        }
+    }
+}
+
+// Walks the module graph backwards from `target`, which is expected to be fed by a single
+// conjunction. Each input of that conjunction has to be an independent part of the circuit that
+// periodically sends a high pulse, the target then receives a low pulse once all of them line up.
+//
+// The periods are measured by simulating a copy of `circuit` for at most `max_presses`.
+pub fn analyze<'a>(
+    circuit: &Circuit<'a>,
+    target: &'a str,
+    max_presses: usize,
+) -> Result<Analysis, AnalysisError<'a>> {
+    let target_id = circuit
+        .id(target)
+        .ok_or(AnalysisError::UnknownTarget(target))?;
+
+    let terminal = match circuit.inputs(target_id) {
+        &[terminal] => terminal,
+        inputs => {
+            return Err(AnalysisError::NotSingleInput {
+                target,
+                inputs: inputs.iter().map(|&i| circuit.name(i)).collect(),
+            })
+        }
+    };
+
+    if circuit.kind(terminal) != Kind::Conjunction {
+        return Err(AnalysisError::NotConjunction {
+            target,
+            module: circuit.name(terminal),
+            kind: circuit.kind(terminal),
+        });
+    }
+
+    let mut counters: Vec<SubCounter> = Vec::new();
+
+    for &output in circuit.inputs(terminal) {
+        let modules = upstream(circuit, output);
+
+        if modules.contains(&terminal) {
+            return Err(AnalysisError::Feedback {
+                counter: circuit.name(output),
+                terminal: circuit.name(terminal),
+            });
+        }
+
+        for other in &counters {
+            if let Some(&shared) = modules.intersection(&other.modules).next() {
+                return Err(AnalysisError::Shared {
+                    counters: (circuit.name(other.output), circuit.name(output)),
+                    module: circuit.name(shared),
+                });
+            }
+        }
+
+        counters.push(SubCounter {
+            output,
+            modules,
+            period: 0,
+        });
+    }
+
+    // the first two presses on which each counter sent a high pulse
+    let mut seen: Vec<Vec<usize>> = vec![Vec::new(); counters.len()];
+
+    let mut simulation = circuit.clone();
+    for _ in 0..max_presses {
+        if seen.iter().all(|s| s.len() >= 2) {
+            break;
+        }
+
+        simulation.press(&mut |press, pulse: Pulse| {
+            if pulse.recipient != terminal || pulse.ptype != PulseType::High {
+                return;
+            }
+
+            let i = counters
+                .iter()
+                .position(|c| c.output == pulse.sender)
+                .unwrap();
+            if seen[i].len() < 2 && seen[i].last() != Some(&press) {
+                seen[i].push(press);
+            }
+        });
+    }
+
+    for (counter, seen) in counters.iter_mut().zip(seen) {
+        let name = circuit.name(counter.output);
+
+        let &[first, second] = &seen[..] else {
+            return Err(AnalysisError::NoHighPulse {
+                counter: name,
+                presses: max_presses,
+            });
+        };
+
+        if second != 2 * first {
+            return Err(AnalysisError::NotPeriodic {
+                counter: name,
+                first,
+                second,
+            });
+        }
+
+        counter.period = first;
+    }
+
+    Ok(Analysis {
+        target: target_id,
+        terminal,
+        counters,
+    })
+}
+
+// every module `output` (transitively) receives pulses from,
+// ignoring the button and broadcaster which every module depends on
+fn upstream(circuit: &Circuit, output: ModuleId) -> HashSet<ModuleId> {
+    let mut result = HashSet::from([output]);
+    let mut stack = vec![output];
+
+    while let Some(module) = stack.pop() {
+        for &input in circuit.inputs(module) {
+            if input == BUTTON || input == BROADCASTER {
+                continue;
+            }
+            if result.insert(input) {
+                stack.push(input);
+            }
+        }
+    }
+
+    result
+}
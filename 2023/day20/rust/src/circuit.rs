@@ -0,0 +1,380 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Write};
+
+use super::state::StateVector;
+use super::{ModuleType, PulseType, Puzzle, State};
+
+pub type ModuleId = usize;
+
+pub const BUTTON: ModuleId = 0;
+pub const BROADCASTER: ModuleId = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Button,
+    Broadcaster,
+    FlipFlop,
+    Conjunction,
+    // named outputs that are only ever sent to, like `rx`
+    Output,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pulse {
+    pub sender: ModuleId,
+    pub ptype: PulseType,
+    pub recipient: ModuleId,
+}
+
+pub trait Observer {
+    // `press` is the number of the button press the pulse belongs to, starting at 1
+    fn observe(&mut self, press: usize, pulse: Pulse);
+}
+
+impl<F: FnMut(usize, Pulse)> Observer for F {
+    fn observe(&mut self, press: usize, pulse: Pulse) {
+        self(press, pulse)
+    }
+}
+
+// for when you don't care about the pulses at all
+impl Observer for () {
+    fn observe(&mut self, _: usize, _: Pulse) {}
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PulseFilter {
+    sender: Option<ModuleId>,
+    ptype: Option<PulseType>,
+    recipient: Option<ModuleId>,
+}
+
+impl PulseFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sender(mut self, sender: ModuleId) -> Self {
+        self.sender = Some(sender);
+        self
+    }
+
+    pub fn ptype(mut self, ptype: PulseType) -> Self {
+        self.ptype = Some(ptype);
+        self
+    }
+
+    pub fn recipient(mut self, recipient: ModuleId) -> Self {
+        self.recipient = Some(recipient);
+        self
+    }
+
+    pub fn matches(&self, pulse: &Pulse) -> bool {
+        self.sender.is_none_or(|s| s == pulse.sender)
+            && self.ptype.is_none_or(|t| t == pulse.ptype)
+            && self.recipient.is_none_or(|r| r == pulse.recipient)
+    }
+
+    // only forwards the pulses matching this filter to `observer`
+    pub fn then<O: Observer>(self, observer: O) -> Filtered<O> {
+        Filtered {
+            filter: self,
+            observer,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Filtered<O> {
+    filter: PulseFilter,
+    observer: O,
+}
+
+impl<O> Filtered<O> {
+    pub fn into_inner(self) -> O {
+        self.observer
+    }
+}
+
+impl<O: Observer> Observer for Vec<O> {
+    fn observe(&mut self, press: usize, pulse: Pulse) {
+        for observer in self.iter_mut() {
+            observer.observe(press, pulse);
+        }
+    }
+}
+
+impl<O: Observer> Observer for Filtered<O> {
+    fn observe(&mut self, press: usize, pulse: Pulse) {
+        if self.filter.matches(&pulse) {
+            self.observer.observe(press, pulse);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PulseCounter {
+    pub low: usize,
+    pub high: usize,
+}
+
+impl Observer for PulseCounter {
+    fn observe(&mut self, _: usize, pulse: Pulse) {
+        match pulse.ptype {
+            PulseType::Low => self.low += 1,
+            PulseType::High => self.high += 1,
+        }
+    }
+}
+
+// writes one line per pulse in the same format as the puzzle description,
+// prefixed with the button press, e.g.
+// `1 button -low-> broadcaster`
+pub struct Trace<'a, W: Write> {
+    names: Vec<&'a str>,
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<'a, W: Write> Trace<'a, W> {
+    pub fn new(circuit: &Circuit<'a>, writer: W) -> Self {
+        Self {
+            names: circuit.names.clone(),
+            writer,
+            error: None,
+        }
+    }
+
+    // returns the first error that occurred while writing, if any
+    pub fn finish(mut self) -> io::Result<W> {
+        match self.error {
+            Some(e) => Err(e),
+            None => self.writer.flush().map(|_| self.writer),
+        }
+    }
+}
+
+impl<W: Write> Observer for Trace<'_, W> {
+    fn observe(&mut self, press: usize, pulse: Pulse) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let ptype = match pulse.ptype {
+            PulseType::Low => "low",
+            PulseType::High => "high",
+        };
+
+        if let Err(e) = writeln!(
+            self.writer,
+            "{} {} -{}-> {}",
+            press, self.names[pulse.sender], ptype, self.names[pulse.recipient]
+        ) {
+            self.error = Some(e);
+        }
+    }
+}
+
+// the puzzle with every module name interned to a `ModuleId`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Circuit<'a> {
+    names: Vec<&'a str>,
+    ids: HashMap<&'a str, ModuleId>,
+    kinds: Vec<Kind>,
+    // every destination together with the slot of this module in the destination's `inputs`
+    destinations: Vec<Vec<(ModuleId, usize)>>,
+    inputs: Vec<Vec<ModuleId>>,
+    // only meaningful for flip-flops
+    on: Vec<bool>,
+    // the last pulse received from each of `inputs`, only meaningful for conjunctions
+    memory: Vec<Vec<PulseType>>,
+    presses: usize,
+    // pending pulses together with the slot of the sender in the recipient's `inputs`
+    queue: VecDeque<(Pulse, usize)>,
+}
+
+impl<'a> Circuit<'a> {
+    pub fn new(puzzle: &Puzzle<'a>) -> Self {
+        let mut names = vec!["button", "broadcaster"];
+        let mut kinds = vec![Kind::Button, Kind::Broadcaster];
+
+        // sorted so that the ids are deterministic
+        let mut modules: Vec<_> = puzzle.modules.values().collect();
+        modules.sort_unstable_by_key(|m| m.name);
+
+        for module in &modules {
+            names.push(module.name);
+            kinds.push(match module.mtype {
+                ModuleType::FlipFlop(_) => Kind::FlipFlop,
+                ModuleType::Conjunction(_) => Kind::Conjunction,
+            });
+        }
+
+        let mut outputs: Vec<&str> = modules
+            .iter()
+            .flat_map(|m| m.destinations.iter().copied())
+            .chain(puzzle.broadcaster.iter().copied())
+            .filter(|d| !puzzle.modules.contains_key(d))
+            .collect();
+        outputs.sort_unstable();
+        outputs.dedup();
+
+        for output in outputs {
+            names.push(output);
+            kinds.push(Kind::Output);
+        }
+
+        let ids: HashMap<&str, ModuleId> = names.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+
+        let mut targets = vec![Vec::new(); names.len()];
+        targets[BUTTON] = vec![BROADCASTER];
+        targets[BROADCASTER] = puzzle.broadcaster.iter().map(|d| ids[d]).collect();
+        for module in &modules {
+            targets[ids[module.name]] = module.destinations.iter().map(|d| ids[d]).collect();
+        }
+
+        let mut inputs = vec![Vec::new(); names.len()];
+        let mut destinations = vec![Vec::new(); names.len()];
+        for (sender, ts) in targets.into_iter().enumerate() {
+            for recipient in ts {
+                destinations[sender].push((recipient, inputs[recipient].len()));
+                inputs[recipient].push(sender);
+            }
+        }
+
+        let mut on = vec![false; names.len()];
+        let mut memory: Vec<Vec<PulseType>> = inputs
+            .iter()
+            .map(|i| vec![PulseType::Low; i.len()])
+            .collect();
+
+        for module in &modules {
+            let id = ids[module.name];
+            match &module.mtype {
+                ModuleType::FlipFlop(state) => on[id] = *state == State::On,
+                ModuleType::Conjunction(m) => {
+                    for (slot, input) in inputs[id].iter().enumerate() {
+                        if let Some(&ptype) = m.get(names[*input]) {
+                            memory[id][slot] = ptype;
+                        }
+                    }
+                }
+            }
+        }
+
+        Self {
+            names,
+            ids,
+            kinds,
+            destinations,
+            inputs,
+            on,
+            memory,
+            presses: 0,
+            queue: VecDeque::new(),
+        }
+    }
+
+    pub fn id(&self, name: &str) -> Option<ModuleId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: ModuleId) -> &'a str {
+        self.names[id]
+    }
+
+    pub fn kind(&self, id: ModuleId) -> Kind {
+        self.kinds[id]
+    }
+
+    pub fn inputs(&self, id: ModuleId) -> &[ModuleId] {
+        &self.inputs[id]
+    }
+
+    pub fn destinations(&self, id: ModuleId) -> impl Iterator<Item = ModuleId> + '_ {
+        self.destinations[id]
+            .iter()
+            .map(|&(recipient, _)| recipient)
+    }
+
+    // every module, including the button and the outputs
+    pub fn ids(&self) -> impl Iterator<Item = ModuleId> {
+        0..self.names.len()
+    }
+
+    // everything that changes when pressing the button, see `StateVector`
+    pub fn snapshot(&self) -> StateVector {
+        let bits = self.on.len() + self.memory.iter().map(Vec::len).sum::<usize>();
+        let mut result = StateVector::with_capacity(bits);
+
+        for (id, kind) in self.kinds.iter().enumerate() {
+            match kind {
+                Kind::FlipFlop => result.push(self.on[id]),
+                Kind::Conjunction => {
+                    for &p in &self.memory[id] {
+                        result.push(p == PulseType::High);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        result
+    }
+
+    // presses the button once and waits for every pulse to be processed,
+    // handing each one to `observer` in the order they are sent
+    pub fn press<O: Observer>(&mut self, observer: &mut O) {
+        self.presses += 1;
+
+        let pulse = Pulse {
+            sender: BUTTON,
+            ptype: PulseType::Low,
+            recipient: BROADCASTER,
+        };
+        self.queue.push_back((pulse, 0));
+
+        while let Some((pulse, slot)) = self.queue.pop_front() {
+            observer.observe(self.presses, pulse);
+            self.receive(pulse, slot);
+        }
+    }
+
+    fn receive(&mut self, pulse: Pulse, slot: usize) {
+        let id = pulse.recipient;
+
+        let ptype = match self.kinds[id] {
+            Kind::Button | Kind::Output => return,
+            Kind::Broadcaster => pulse.ptype,
+            Kind::FlipFlop => match pulse.ptype {
+                PulseType::High => return, // nothing happens
+                PulseType::Low => {
+                    self.on[id] = !self.on[id];
+                    if self.on[id] {
+                        PulseType::High
+                    } else {
+                        PulseType::Low
+                    }
+                }
+            },
+            Kind::Conjunction => {
+                self.memory[id][slot] = pulse.ptype;
+
+                if self.memory[id].iter().all(|&p| p == PulseType::High) {
+                    PulseType::Low
+                } else {
+                    PulseType::High
+                }
+            }
+        };
+
+        for &(recipient, slot) in &self.destinations[id] {
+            let pulse = Pulse {
+                sender: id,
+                ptype,
+                recipient,
+            };
+            self.queue.push_back((pulse, slot));
+        }
+    }
+}
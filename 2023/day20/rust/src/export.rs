@@ -0,0 +1,75 @@
+use std::fmt::Write;
+
+use super::circuit::{Circuit, Kind, ModuleId, BUTTON};
+
+// every module except the button, in id order so the output is deterministic
+fn modules(circuit: &Circuit) -> impl Iterator<Item = ModuleId> {
+    circuit.ids().filter(|&id| id != BUTTON)
+}
+
+// `cat input.txt | cargo run -- --dot | dot -Tsvg > modules.svg`
+pub fn to_dot(circuit: &Circuit) -> String {
+    let mut result = String::new();
+
+    writeln!(result, "digraph modules {{").unwrap();
+
+    for id in modules(circuit) {
+        let name = circuit.name(id);
+        let attributes = match circuit.kind(id) {
+            Kind::Button | Kind::Broadcaster => format!("label=\"{}\", shape=doublecircle", name),
+            Kind::FlipFlop => format!("label=\"%{}\", shape=box", name),
+            Kind::Conjunction => format!("label=\"&{}\", shape=invtriangle", name),
+            Kind::Output => format!("label=\"{}\", shape=doubleoctagon", name),
+        };
+        writeln!(result, "    {} [{}];", name, attributes).unwrap();
+    }
+
+    for id in modules(circuit) {
+        for destination in circuit.destinations(id) {
+            writeln!(
+                result,
+                "    {} -> {};",
+                circuit.name(id),
+                circuit.name(destination)
+            )
+            .unwrap();
+        }
+    }
+
+    writeln!(result, "}}").unwrap();
+
+    result
+}
+
+// can be pasted into anything that renders mermaid, e.g. a markdown code block on GitHub
+pub fn to_mermaid(circuit: &Circuit) -> String {
+    let mut result = String::new();
+
+    writeln!(result, "flowchart LR").unwrap();
+
+    for id in modules(circuit) {
+        let name = circuit.name(id);
+        // quoted labels because `%` and `&` have special meaning otherwise
+        let node = match circuit.kind(id) {
+            Kind::Button | Kind::Broadcaster => format!("(({}))", name),
+            Kind::FlipFlop => format!("[\"%{}\"]", name),
+            Kind::Conjunction => format!("{{\"&{}\"}}", name),
+            Kind::Output => format!("[/{}/]", name),
+        };
+        writeln!(result, "    {}{}", name, node).unwrap();
+    }
+
+    for id in modules(circuit) {
+        for destination in circuit.destinations(id) {
+            writeln!(
+                result,
+                "    {} --> {}",
+                circuit.name(id),
+                circuit.name(destination)
+            )
+            .unwrap();
+        }
+    }
+
+    result
+}
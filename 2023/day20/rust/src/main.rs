@@ -1,10 +1,15 @@
-use num::integer::lcm;
 use std::{
-    collections::{HashMap, VecDeque},
-    io::{Error, Read},
+    collections::HashMap,
+    io::{Error, ErrorKind, Read},
     ops::{Deref, DerefMut, Not},
 };
 
+mod analysis;
+mod circuit;
+mod export;
+mod state;
+use circuit::{Circuit, PulseCounter, PulseFilter, Trace};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum State {
     Off,
@@ -64,54 +69,6 @@ struct Module<'a> {
     destinations: Vec<&'a str>,
 }
 
-impl<'a> Module<'a> {
-    fn send_signal(&mut self, ptype: PulseType, sender: &str) -> Vec<Pulse<'a>> {
-        match self.mtype {
-            ModuleType::FlipFlop(ref mut state) => {
-                match ptype {
-                    PulseType::Low => {
-                        let ptype = match state {
-                            State::Off => PulseType::High,
-                            State::On => PulseType::Low,
-                        };
-
-                        *state = !*state;
-
-                        self.destinations
-                            .iter()
-                            .map(|recipient| Pulse {
-                                sender: self.name,
-                                ptype,
-                                recipient,
-                            })
-                            .collect()
-                    }
-                    PulseType::High => vec![], // nothing happens
-                }
-            }
-            ModuleType::Conjunction(ref mut memory) => {
-                let pulse = memory.get_mut(sender).unwrap();
-                *pulse = ptype;
-
-                let ptype = if memory.values().all(|p| *p == PulseType::High) {
-                    PulseType::Low
-                } else {
-                    PulseType::High
-                };
-
-                self.destinations
-                    .iter()
-                    .map(|recipient| Pulse {
-                        sender: self.name,
-                        ptype,
-                        recipient,
-                    })
-                    .collect()
-            }
-        }
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Puzzle<'a> {
     broadcaster: Vec<&'a str>,
@@ -135,18 +92,11 @@ impl Not for PulseType {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Pulse<'a> {
-    sender: &'a str,
-    ptype: PulseType,
-    recipient: &'a str,
-}
-
 fn parse_destinations(destinations: &str) -> Vec<&str> {
     destinations.split(", ").collect()
 }
 
-fn parse_input(input: &str) -> Puzzle {
+fn parse_input(input: &str) -> Puzzle<'_> {
     let mut map = HashMap::new();
     let mut broadcaster: Option<Vec<&str>> = None;
 
@@ -197,147 +147,149 @@ fn parse_input(input: &str) -> Puzzle {
     }
 }
 
-fn press_button<'a>(puzzle: &mut Puzzle<'a>) -> Vec<Pulse<'a>> {
-    let mut pulses = VecDeque::new();
-    let mut result = Vec::new();
-
-    for module in &puzzle.broadcaster {
-        pulses.extend(
-            puzzle
-                .modules
-                .get_mut(module)
-                .unwrap()
-                .send_signal(PulseType::Low, module),
-        );
-
-        result.push(Pulse {
-            sender: "broadcaster",
-            ptype: PulseType::Low,
-            recipient: module,
-        });
-    }
-
-    while let Some(pulse) = pulses.pop_front() {
-        if let Some(m) = puzzle.modules.get_mut(pulse.recipient) {
-            pulses.extend(m.send_signal(pulse.ptype, pulse.sender));
-        } // else the module is a named output
+// the pulses sent during `presses` button presses
+//
+// as soon as the circuit returns to an earlier state,
+// the rest is extrapolated from the pulses sent during that period
+fn count_pulses(puzzle: &Puzzle, presses: usize) -> PulseCounter {
+    let mut circuit = Circuit::new(puzzle);
+
+    // prefix[i] = pulses sent during the first i presses
+    let mut prefix = vec![PulseCounter::default()];
+    let mut seen = HashMap::from([(circuit.snapshot(), 0)]);
+
+    for i in 1..=presses {
+        let mut counter = prefix[i - 1];
+        circuit.press(&mut counter);
+        prefix.push(counter);
+
+        let state = circuit.snapshot();
+        if let Some(&start) = seen.get(&state) {
+            let period = i - start;
+            let cycles = (presses - start) / period;
+            let remainder = (presses - start) % period;
+
+            let (before, after, rest) = (prefix[start], prefix[i], prefix[start + remainder]);
+
+            return PulseCounter {
+                low: before.low + cycles * (after.low - before.low) + (rest.low - before.low),
+                high: before.high + cycles * (after.high - before.high) + (rest.high - before.high),
+            };
+        }
 
-        result.push(pulse);
+        seen.insert(state, i);
     }
 
-    result
+    prefix[presses]
 }
 
-fn cycle<'a>(puzzle: &'a mut Puzzle, cycles: usize) -> usize {
-    let mut log: Vec<Pulse> = Vec::new();
-
-    for _ in 0..cycles {
-        log.extend(press_button(puzzle));
-    }
-
-    // + cycles for the button pulse(s)
-    let low_pulses = log.iter().filter(|p| p.ptype == PulseType::Low).count() + cycles;
-    let high_pulses = log.iter().filter(|p| p.ptype == PulseType::High).count();
+fn cycle(puzzle: &Puzzle, cycles: usize) -> usize {
+    let counter = count_pulses(puzzle, cycles);
 
-    low_pulses * high_pulses
+    counter.low * counter.high
 }
 
 fn part1(input: &str) -> usize {
-    let mut puzzle = parse_input(input);
+    let puzzle = parse_input(input);
 
-    cycle(&mut puzzle, 1000)
+    cycle(&puzzle, 1000)
 }
 
 fn part2(input: &str) -> usize {
-    let mut puzzle = parse_input(input);
-
-    // So basically you were supposed to figure out that in the input,
-    // the only way for rx to get a signal is by getting it from a single conjunction
-    // (for my input that conjunction is called gf).
-    // Then you should figure out that this conjunction only depends on 4 other conjunctions
-    // (in my case called sp, pg, sv and qs) and if you look further,
-    // then you'll even notive that these each only depend on 1 conjunction
-    // (rn, pz, jt and mh for my input),
-    // though that last fact seems irrelevant.
-    //
-    // What you can then do is measure how long it takes for the 4 inputs of gf to send a high
-    // pulse, because for gf to send a low pulse, all it needs is to get a high pulse from all of
-    // its 4 inputs. The actual result is then the least common multiple of those 4 results.
+    let puzzle = parse_input(input);
+    let circuit = Circuit::new(&puzzle);
+
+    // The only way for rx to get a low pulse is from a single conjunction
+    // (for my input that conjunction is called gf),
+    // which only sends one once it has received a high pulse from all of its inputs
+    // (in my case sp, pg, sv and qs).
+    // Each of those inputs is the output of an independent part of the circuit
+    // that acts like a counter, sending a high pulse every n button presses.
+    // The actual result is then the least common multiple of those periods.
     //
-    // Also notice how we don't need to actually need to use an lcm function,
-    // as those 4 inputs seem to be coprime.
-    // However, I did anyway as I think it's nicer.
-
-    // find out the 4 dependents
-    let rx_dependency = puzzle
-        .modules
-        .values()
-        .find(|module| module.destinations.contains(&"rx"))
-        .unwrap();
-
-    let dependents: Vec<Module> = puzzle
-        .modules
-        .values()
-        .filter(|module| module.destinations.contains(&rx_dependency.name))
-        .cloned()
-        .collect();
-
-    // if it's not of length 4, then fuck
-    assert!(dependents.len() == 4);
-
-    let mut counter = 0;
-    let mut d0 = 0;
-    let mut d1 = 0;
-    let mut d2 = 0;
-    let mut d3 = 0;
-    loop {
-        counter += 1;
-
-        let pulses = press_button(&mut puzzle);
-
-        if d0 == 0
-            && pulses
-                .iter()
-                .any(|p| p.sender == dependents[0].name && p.ptype == PulseType::High)
-        {
-            d0 = counter;
-        }
+    // `analyze` checks that the input actually has this structure
+    // and measures the periods.
+    match analysis::analyze(&circuit, "rx", 1 << 16) {
+        Ok(analysis) => analysis.presses(),
+        Err(e) => panic!("{}", e),
+    }
+}
 
-        if d1 == 0
-            && pulses
-                .iter()
-                .any(|p| p.sender == dependents[1].name && p.ptype == PulseType::High)
-        {
-            d1 = counter;
-        }
+fn main() -> Result<(), Error> {
+    let mut input = String::new();
+    let _ = std::io::stdin().read_to_string(&mut input)?;
 
-        if d2 == 0
-            && pulses
-                .iter()
-                .any(|p| p.sender == dependents[2].name && p.ptype == PulseType::High)
-        {
-            d2 = counter;
+    // `cargo run -- --trace 1000 --from broadcaster --pulse high < input.txt > trace.txt`
+    let args: Vec<String> = std::env::args().collect();
+    let invalid = |message: String| Error::new(ErrorKind::InvalidInput, message);
+    // the value following `flag`, an error if `flag` is the last argument
+    let arg = |flag: &str| -> Result<Option<&str>, Error> {
+        match args.iter().position(|arg| arg == flag) {
+            Some(i) => match args.get(i + 1) {
+                Some(value) => Ok(Some(value.as_str())),
+                None => Err(invalid(format!("{} expects a value", flag))),
+            },
+            None => Ok(None),
         }
+    };
 
-        if d3 == 0
-            && pulses
-                .iter()
-                .any(|p| p.sender == dependents[3].name && p.ptype == PulseType::High)
-        {
-            d3 = counter;
-        }
+    if args.iter().any(|arg| arg == "--dot") {
+        print!("{}", export::to_dot(&Circuit::new(&parse_input(&input))));
+        return Ok(());
+    }
 
-        if d0 > 0 && d1 > 0 && d2 > 0 && d3 > 0 {
-            break;
-        }
+    if args.iter().any(|arg| arg == "--mermaid") {
+        print!(
+            "{}",
+            export::to_mermaid(&Circuit::new(&parse_input(&input)))
+        );
+        return Ok(());
     }
 
-    lcm(lcm(d0, d1), lcm(d2, d3))
-}
+    if let Some(presses) = arg("--trace")? {
+        let presses: usize = presses.parse().map_err(|_| {
+            invalid(format!(
+                "--trace expects a number of presses, got `{}`",
+                presses
+            ))
+        })?;
+
+        let puzzle = parse_input(&input);
+        let mut circuit = Circuit::new(&puzzle);
+
+        let module = |name: &str| {
+            circuit
+                .id(name)
+                .ok_or_else(|| invalid(format!("unknown module `{}`", name)))
+        };
+
+        let mut filter = PulseFilter::new();
+        if let Some(name) = arg("--from")? {
+            filter = filter.sender(module(name)?);
+        }
+        if let Some(name) = arg("--to")? {
+            filter = filter.recipient(module(name)?);
+        }
+        match arg("--pulse")? {
+            Some("low") => filter = filter.ptype(PulseType::Low),
+            Some("high") => filter = filter.ptype(PulseType::High),
+            Some(pulse) => {
+                return Err(invalid(format!(
+                    "--pulse has to be either `low` or `high`, got `{}`",
+                    pulse
+                )))
+            }
+            None => {}
+        }
 
-fn main() -> Result<(), Error> {
-    let mut input = String::new();
-    let _ = std::io::stdin().read_to_string(&mut input)?;
+        let mut trace = filter.then(Trace::new(&circuit, std::io::stdout().lock()));
+        for _ in 0..presses {
+            circuit.press(&mut trace);
+        }
+
+        let _ = trace.into_inner().finish()?;
+        return Ok(());
+    }
 
     println!("Part 1: {}", part1(&input));
     println!("Part 2: {}", part2(&input));
@@ -378,4 +330,216 @@ mod tests {
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn test_trace() {
+        let puzzle = parse_input(EXAMPLE2);
+        let mut circuit = Circuit::new(&puzzle);
+        let mut trace = Trace::new(&circuit, Vec::new());
+
+        circuit.press(&mut trace);
+
+        let expected = "1 button -low-> broadcaster
+1 broadcaster -low-> a
+1 a -high-> inv
+1 a -high-> con
+1 inv -low-> b
+1 con -high-> output
+1 b -high-> con
+1 con -low-> output
+";
+        let actual = String::from_utf8(trace.finish().unwrap()).unwrap();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_filter() {
+        let puzzle = parse_input(EXAMPLE2);
+        let mut circuit = Circuit::new(&puzzle);
+
+        let output = circuit.id("output").unwrap();
+        let mut low_outputs = Vec::new();
+        let mut observer = PulseFilter::new()
+            .recipient(output)
+            .ptype(PulseType::Low)
+            .then(|press, _| low_outputs.push(press));
+
+        for _ in 0..4 {
+            circuit.press(&mut observer);
+        }
+
+        assert_eq!(low_outputs, vec![1, 3]);
+    }
+
+    // three independent flip-flop chains of length 1, 2 and 3, each inverted into gf
+    const COUNTERS: &str = "broadcaster -> a1, b1, c1
+%a1 -> ia
+&ia -> gf
+%b1 -> b2
+%b2 -> ib
+&ib -> gf
+%c1 -> c2
+%c2 -> c3
+%c3 -> ic
+&ic -> gf
+&gf -> rx
+";
+
+    #[test]
+    fn test_analyze() {
+        let puzzle = parse_input(COUNTERS);
+        let circuit = Circuit::new(&puzzle);
+
+        let analysis = analysis::analyze(&circuit, "rx", 100).unwrap();
+
+        let periods: Vec<usize> = analysis.counters.iter().map(|c| c.period).collect();
+        assert_eq!(periods, vec![2, 4, 8]);
+        assert_eq!(circuit.name(analysis.terminal), "gf");
+        assert_eq!(analysis.presses(), 8);
+
+        // compare against brute force
+        let mut circuit = circuit;
+        let rx = circuit.id("rx").unwrap();
+        let mut first = None;
+        while first.is_none() {
+            circuit.press(
+                &mut PulseFilter::new()
+                    .recipient(rx)
+                    .ptype(PulseType::Low)
+                    .then(|press, _| {
+                        first.get_or_insert(press);
+                    }),
+            );
+        }
+        assert_eq!(first, Some(8));
+    }
+
+    #[test]
+    fn test_analyze_errors() {
+        use analysis::AnalysisError;
+
+        let puzzle = parse_input(COUNTERS);
+        let circuit = Circuit::new(&puzzle);
+        assert_eq!(
+            analysis::analyze(&circuit, "zz", 100),
+            Err(AnalysisError::UnknownTarget("zz"))
+        );
+        assert_eq!(
+            analysis::analyze(&circuit, "c2", 100),
+            Err(AnalysisError::NotConjunction {
+                target: "c2",
+                module: "c1",
+                kind: circuit::Kind::FlipFlop
+            })
+        );
+
+        let input = COUNTERS.replace("%b1 -> b2", "%b1 -> b2, c2");
+        let puzzle = parse_input(&input);
+        let circuit = Circuit::new(&puzzle);
+        assert_eq!(
+            analysis::analyze(&circuit, "rx", 100),
+            Err(AnalysisError::Shared {
+                counters: ("ib", "ic"),
+                module: "b1"
+            })
+        );
+
+        let input = COUNTERS.replace("&gf -> rx", "&gf -> rx, a1");
+        let puzzle = parse_input(&input);
+        let circuit = Circuit::new(&puzzle);
+        let error = analysis::analyze(&circuit, "rx", 100).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`ia` is not independent, it depends on the output of `gf`"
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let puzzle = parse_input(EXAMPLE2);
+
+        let expected = "digraph modules {
+    broadcaster [label=\"broadcaster\", shape=doublecircle];
+    a [label=\"%a\", shape=box];
+    b [label=\"%b\", shape=box];
+    con [label=\"&con\", shape=invtriangle];
+    inv [label=\"&inv\", shape=invtriangle];
+    output [label=\"output\", shape=doubleoctagon];
+    broadcaster -> a;
+    a -> inv;
+    a -> con;
+    b -> con;
+    con -> output;
+    inv -> b;
+}
+";
+
+        assert_eq!(expected, export::to_dot(&Circuit::new(&puzzle)));
+    }
+
+    #[test]
+    fn test_to_mermaid() {
+        let puzzle = parse_input(EXAMPLE2);
+
+        let expected = "flowchart LR
+    broadcaster((broadcaster))
+    a[\"%a\"]
+    b[\"%b\"]
+    con{\"&con\"}
+    inv{\"&inv\"}
+    output[/output/]
+    broadcaster --> a
+    a --> inv
+    a --> con
+    b --> con
+    con --> output
+    inv --> b
+";
+
+        assert_eq!(expected, export::to_mermaid(&Circuit::new(&puzzle)));
+    }
+
+    #[test]
+    fn test_count_pulses() {
+        for input in [EXAMPLE, EXAMPLE2] {
+            let puzzle = parse_input(input);
+
+            for presses in [0, 1, 2, 3, 4, 5, 1000, 1003] {
+                let mut circuit = Circuit::new(&puzzle);
+                let mut expected = PulseCounter::default();
+                for _ in 0..presses {
+                    circuit.press(&mut expected);
+                }
+
+                assert_eq!(expected, count_pulses(&puzzle, presses));
+            }
+        }
+
+        let puzzle = parse_input(EXAMPLE);
+        let expected = PulseCounter {
+            low: 8_000_000_000,
+            high: 4_000_000_000,
+        };
+        assert_eq!(expected, count_pulses(&puzzle, 1_000_000_000));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let puzzle = parse_input(EXAMPLE2);
+        let mut circuit = Circuit::new(&puzzle);
+        let initial = circuit.snapshot();
+
+        let mut states = vec![];
+        for _ in 0..4 {
+            circuit.press(&mut ());
+            states.push(circuit.snapshot());
+        }
+
+        // the circuit needs 4 presses to return to its initial state
+        assert_ne!(states[0], initial);
+        assert_ne!(states[1], initial);
+        assert_ne!(states[2], initial);
+        assert_eq!(states[3], initial);
+    }
 }
@@ -0,0 +1,29 @@
+// The whole state of a circuit packed into bits,
+// one per flip-flop and one per input of every conjunction.
+//
+// Two circuits built from the same puzzle are in the same state
+// if and only if their state vectors are equal.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct StateVector {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl StateVector {
+    pub fn with_capacity(bits: usize) -> Self {
+        Self {
+            words: Vec::with_capacity(bits.div_ceil(64)),
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, bit: bool) {
+        if self.len.is_multiple_of(64) {
+            self.words.push(0);
+        }
+        if bit {
+            self.words[self.len / 64] |= 1 << (self.len % 64);
+        }
+        self.len += 1;
+    }
+}
@@ -1,10 +1,10 @@
 use std::{
-    fmt::Debug,
+    fmt::Display,
     io::{Error, Read},
-    ops::{Index, IndexMut},
 };
+use utils::grid;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Tile {
     Start,
     Plot,
@@ -12,7 +12,7 @@ enum Tile {
     O,
 }
 
-impl Debug for Tile {
+impl Display for Tile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Tile::Start => write!(f, "S"),
@@ -37,172 +37,35 @@ impl TryFrom<char> for Tile {
     }
 }
 
-#[derive(Debug, Clone)]
-struct Grid {
-    grid: Vec<Vec<Tile>>,
-    rows: usize,
-    columns: usize,
-}
-
-impl Grid {
-    fn lines(&self) -> GridIterator {
-        GridIterator {
-            grid: self,
-            current_row: 0,
-        }
-    }
-
-    fn get(&self, (x, y): (usize, usize)) -> Option<&Tile> {
-        if x < self.columns && y < self.rows {
-            Some(&self.grid[y][x])
-        } else {
-            None
-        }
-    }
-
-    fn get_mut(&mut self, (x, y): (usize, usize)) -> Option<&mut Tile> {
-        if x < self.columns && y < self.rows {
-            Some(&mut self.grid[y][x])
-        } else {
-            None
-        }
-    }
-
-    // naming things is hard
-    // (see part 2 comments for what this does)
-    fn make_big(&mut self) {
-        // find s
-        let mut s_pos = (0, 0);
-        for (j, line) in self.grid.iter().enumerate() {
-            for (i, tile) in line.iter().enumerate() {
-                if *tile == Tile::Start {
-                    s_pos = (i, j);
-                    break;
-                }
-            }
-        }
-
-        // remove s
-        self[s_pos] = Tile::Plot;
-
-        // extend horizontally
-        for line in self.grid.iter_mut() {
-            let clone = line.clone();
-
-            line.extend(clone.clone());
-            line.extend(clone.clone());
-            line.extend(clone.clone());
-            line.extend(clone);
-        }
-
-        // extend vertically
-        let clone = self.grid.clone();
-        self.grid.extend(clone.clone());
-        self.grid.extend(clone.clone());
-        self.grid.extend(clone.clone());
-        self.grid.extend(clone);
-
-        // add s back in
-        self.grid[self.rows * 2 + s_pos.1][self.columns * 2 + s_pos.0] = Tile::Start;
+type Grid = grid::Grid<Tile>;
 
-        self.columns *= 5;
-        self.rows *= 5;
-    }
-}
-
-impl TryFrom<&str> for Grid {
-    type Error = String;
-
-    fn try_from(input: &str) -> Result<Self, Self::Error> {
-        let trimmed = input.trim();
-
-        let rows = trimmed.lines().count();
-
-        let first_line = trimmed.lines().next().ok_or("input is empty")?;
-        let columns = first_line.len();
-
-        let mut grid = Vec::with_capacity(rows);
-
-        for line in trimmed.lines() {
-            if line.len() != columns {
-                return Err("not a grid".into());
-            }
+// naming things is hard
+// (see part 2 comments for what this does)
+fn make_big(grid: &Grid) -> Grid {
+    let s_pos = grid.position(|&t| t == Tile::Start).unwrap();
 
-            let tiles = line
-                .chars()
-                .map(Tile::try_from)
-                .collect::<Result<Vec<Tile>, _>>()?;
-            grid.push(tiles);
-        }
-
-        Ok(Grid {
-            grid,
-            rows,
-            columns,
-        })
-    }
-}
-
-struct GridIterator<'a> {
-    grid: &'a Grid,
-    current_row: usize,
-}
+    let mut plain = grid.clone();
+    plain[s_pos] = Tile::Plot;
 
-impl<'a> Iterator for GridIterator<'a> {
-    type Item = &'a [Tile];
+    let (rows, columns) = (grid.rows(), grid.columns());
+    let mut big = Grid::new(vec![Tile::Plot; rows * columns * 25], rows * 5, columns * 5);
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.current_row < self.grid.rows {
-            let r = Some(&self.grid[self.current_row]);
-            self.current_row += 1;
-            r
-        } else {
-            None
+    for y in 0..5 {
+        for x in 0..5 {
+            big.paste((columns * x, rows * y), &plain).unwrap();
         }
     }
-}
-
-impl Index<(usize, usize)> for Grid {
-    type Output = Tile;
-
-    fn index(&self, index: (usize, usize)) -> &Self::Output {
-        self.get(index).unwrap()
-    }
-}
-
-impl IndexMut<(usize, usize)> for Grid {
-    fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
-        self.get_mut(index).unwrap()
-    }
-}
-
-impl Index<usize> for Grid {
-    type Output = [Tile];
 
-    fn index(&self, index: usize) -> &Self::Output {
-        &self.grid[index]
-    }
-}
+    // only keep the s in the middle
+    big[(columns * 2 + s_pos.0, rows * 2 + s_pos.1)] = Tile::Start;
 
-impl IndexMut<usize> for Grid {
-    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        &mut self.grid[index]
-    }
+    big
 }
 
 fn take_step(grid: &mut Grid) {
-    let mut starts = Vec::new();
-    grid.lines().enumerate().for_each(|(row, tiles)| {
-        starts.extend(
-            tiles
-                .iter()
-                .enumerate()
-                .filter_map(|(column, tile)| match tile {
-                    Tile::Start | Tile::O => Some((column, row)),
-                    Tile::Plot | Tile::Rock => None,
-                }),
-        );
-    });
+    let starts: Vec<(usize, usize)> = grid
+        .positions(|&t| matches!(t, Tile::Start | Tile::O))
+        .collect();
 
     // first mark all starts as plots
     for &start in &starts {
@@ -254,27 +117,25 @@ fn take_step(grid: &mut Grid) {
 }
 
 fn count_os(grid: &Grid) -> usize {
-    grid.lines()
-        .map(|tiles| {
-            tiles
-                .iter()
-                .filter_map(|tile| match tile {
-                    Tile::Start | Tile::O => Some(1),
-                    _ => None,
-                })
-                .sum::<usize>()
-        })
-        .sum()
+    grid.positions(|&t| matches!(t, Tile::Start | Tile::O))
+        .count()
 }
 
+// As every step can be undone by stepping back, a plot can be reached in exactly `steps` steps if
+// it can be reached in fewer steps with the same parity.
 fn part1(input: &str, steps: usize) -> usize {
-    let mut grid = Grid::try_from(input).unwrap();
+    let grid = Grid::try_from(input).unwrap();
 
-    for _ in 0..steps {
-        take_step(&mut grid);
-    }
+    let start = grid.position(|&t| t == Tile::Start).unwrap();
+
+    let distances = grid.distance_map(start, |&t| t != Tile::Rock, |p| grid.neighbours(p));
 
-    count_os(&grid)
+    distances
+        .grid()
+        .iter()
+        .flatten()
+        .filter(|&&d| d as usize <= steps && d as usize % 2 == steps % 2)
+        .count()
 }
 
 fn aitken_neville(v0: usize, v1: usize, v2: usize, x: usize) -> usize {
@@ -313,12 +174,12 @@ fn aitken_neville(v0: usize, v1: usize, v2: usize, x: usize) -> usize {
 // the Aitken Neville scheme fit well.
 // The code of Aitken Neville above is copied from lecture slides of mine.
 fn part2(input: &str) -> usize {
-    let mut grid = Grid::try_from(input).unwrap();
+    let grid = Grid::try_from(input).unwrap();
 
     // To find the values of the first 3 xs, we first need to make the grid sufficiently large.
     // `make_big` just extends the grid by 5 in each direction.
     // 5 is just a random value that turned out to be enough.
-    grid.make_big();
+    let mut grid = make_big(&grid);
 
     for _ in 0..65 {
         take_step(&mut grid);
@@ -357,6 +218,7 @@ fn main() -> Result<(), Error> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use utils::grid::Rect;
 
     const EXAMPLE: &str = "...........
 .....###.#.
@@ -371,6 +233,61 @@ mod tests {
 ...........
 ";
 
+    #[test]
+    fn test_take_step() {
+        let expected = "...........
+.....###.#.
+.###.##..#.
+..#.#O..#..
+....#.#....
+.##O.O####.
+.##.O#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........
+";
+
+        let mut grid = Grid::try_from(EXAMPLE).unwrap();
+        take_step(&mut grid);
+        take_step(&mut grid);
+
+        assert_eq!(expected, grid.to_string());
+    }
+
+    #[test]
+    fn test_make_big() {
+        let grid = Grid::try_from(EXAMPLE).unwrap();
+        let big = make_big(&grid);
+
+        assert_eq!((55, 55), (big.rows(), big.columns()));
+        assert_eq!(Tile::Start, big[(27usize, 27usize)]);
+        assert_eq!(1, count_os(&big));
+
+        let center = Rect::new((22, 22), 11, 11);
+        assert_eq!(grid, big.view(center).unwrap().to_owned());
+
+        let rendered = big.to_string();
+        assert_eq!(
+            Some(".##..#...#.".repeat(5).as_str()),
+            rendered.lines().nth(6)
+        );
+        assert_eq!(
+            Some(".##...####.".repeat(5).as_str()),
+            rendered.lines().nth(5)
+        );
+    }
+
+    #[test]
+    fn test_part1_take_step() {
+        let mut grid = Grid::try_from(EXAMPLE).unwrap();
+
+        for steps in 0..30 {
+            assert_eq!(count_os(&grid), part1(EXAMPLE, steps), "{} steps", steps);
+            take_step(&mut grid);
+        }
+    }
+
     #[test]
     fn test_part1() {
         let expected = 16;
@@ -34,54 +34,34 @@ impl TryFrom<char> for Tile {
 
 type Grid = utils::grid::Grid<Tile>;
 
+// the direction of every step and how it changes the position
+const STEPS: [(Direction, (isize, isize)); 4] = [
+    (Direction::Up, (0, -1)),
+    (Direction::Down, (0, 1)),
+    (Direction::Left, (-1, 0)),
+    (Direction::Right, (1, 0)),
+];
+
 fn walk_path_part1(grid: &mut Grid, (x, y): (usize, usize), path: usize, paths: &mut Vec<usize>) {
     if y == grid.rows() - 1 {
         paths.push(path);
         return;
     }
 
-    // check up
-    match grid[y - 1][x] {
-        Tile::Path | Tile::Slope(Direction::Up) => {
-            let tile_before = grid[y - 1][x];
-            grid[y - 1][x] = Tile::Walked;
-            walk_path_part1(grid, (x, y - 1), path + 1, paths);
-            grid[y - 1][x] = tile_before;
-        }
-        _ => {}
-    }
+    for (direction, (dx, dy)) in STEPS {
+        let next = (x as isize + dx, y as isize + dy);
 
-    // check down
-    match grid[y + 1][x] {
-        Tile::Path | Tile::Slope(Direction::Down) => {
-            let tile_before = grid[y + 1][x];
-            grid[y + 1][x] = Tile::Walked;
-            walk_path_part1(grid, (x, y + 1), path + 1, paths);
-            grid[y + 1][x] = tile_before;
+        // outside of the grid is just more forest
+        match *grid.get_or(next, &Tile::Forest) {
+            Tile::Path => {}
+            Tile::Slope(d) if d == direction => {}
+            _ => continue,
         }
-        _ => {}
-    }
 
-    // check left
-    match grid[y][x - 1] {
-        Tile::Path | Tile::Slope(Direction::Left) => {
-            let tile_before = grid[y][x - 1];
-            grid[y][x - 1] = Tile::Walked;
-            walk_path_part1(grid, (x - 1, y), path + 1, paths);
-            grid[y][x - 1] = tile_before;
-        }
-        _ => {}
-    }
-
-    // check right
-    match grid[y][x + 1] {
-        Tile::Path | Tile::Slope(Direction::Right) => {
-            let tile_before = grid[y][x + 1];
-            grid[y][x + 1] = Tile::Walked;
-            walk_path_part1(grid, (x + 1, y), path + 1, paths);
-            grid[y][x + 1] = tile_before;
-        }
-        _ => {}
+        let tile_before = grid[next];
+        grid[next] = Tile::Walked;
+        walk_path_part1(grid, (next.0 as usize, next.1 as usize), path + 1, paths);
+        grid[next] = tile_before;
     }
 }
 
@@ -91,67 +71,31 @@ fn walk_path_part2(grid: &mut Grid, (x, y): (usize, usize), path: usize, paths:
         return;
     }
 
-    // check up
-    match grid[y - 1][x] {
-        Tile::Path | Tile::Slope(_) => {
-            let tile_before = grid[y - 1][x];
-            grid[y - 1][x] = Tile::Walked;
-            walk_path_part2(grid, (x, y - 1), path + 1, paths);
-            grid[y - 1][x] = tile_before;
-        }
-        _ => {}
-    }
-
-    // check down
-    match grid[y + 1][x] {
-        Tile::Path | Tile::Slope(_) => {
-            let tile_before = grid[y + 1][x];
-            grid[y + 1][x] = Tile::Walked;
-            walk_path_part2(grid, (x, y + 1), path + 1, paths);
-            grid[y + 1][x] = tile_before;
-        }
-        _ => {}
-    }
+    for (_, (dx, dy)) in STEPS {
+        let next = (x as isize + dx, y as isize + dy);
 
-    // check left
-    match grid[y][x - 1] {
-        Tile::Path | Tile::Slope(_) => {
-            let tile_before = grid[y][x - 1];
-            grid[y][x - 1] = Tile::Walked;
-            walk_path_part2(grid, (x - 1, y), path + 1, paths);
-            grid[y][x - 1] = tile_before;
+        match *grid.get_or(next, &Tile::Forest) {
+            Tile::Path | Tile::Slope(_) => {}
+            _ => continue,
         }
-        _ => {}
-    }
 
-    // check right
-    match grid[y][x + 1] {
-        Tile::Path | Tile::Slope(_) => {
-            let tile_before = grid[y][x + 1];
-            grid[y][x + 1] = Tile::Walked;
-            walk_path_part2(grid, (x + 1, y), path + 1, paths);
-            grid[y][x + 1] = tile_before;
-        }
-        _ => {}
+        let tile_before = grid[next];
+        grid[next] = Tile::Walked;
+        walk_path_part2(grid, (next.0 as usize, next.1 as usize), path + 1, paths);
+        grid[next] = tile_before;
     }
 }
 
 fn part1(input: &str) -> usize {
     let mut grid = Grid::try_from(input).unwrap();
 
-    let start_index = grid[0]
-        .iter()
-        .enumerate()
-        .find(|&(_, &t)| t == Tile::Path)
-        .unwrap()
-        .0;
-
-    grid[0][start_index] = Tile::Walked;
+    // the first path row by row is the one in the top row
+    let start = grid.position(|&t| t == Tile::Path).unwrap();
 
-    let (x, y) = (start_index, 1usize);
+    grid[start] = Tile::Walked;
 
     let mut result = Vec::new();
-    walk_path_part1(&mut grid, (x, y), 1, &mut result);
+    walk_path_part1(&mut grid, start, 0, &mut result);
 
     *result.iter().max().unwrap()
 }
@@ -159,19 +103,13 @@ fn part1(input: &str) -> usize {
 fn part2(input: &str) -> usize {
     let mut grid = Grid::try_from(input).unwrap();
 
-    let start_index = grid[0]
-        .iter()
-        .enumerate()
-        .find(|&(_, &t)| t == Tile::Path)
-        .unwrap()
-        .0;
-
-    grid[0][start_index] = Tile::Walked;
+    // the first path row by row is the one in the top row
+    let start = grid.position(|&t| t == Tile::Path).unwrap();
 
-    let (x, y) = (start_index, 1usize);
+    grid[start] = Tile::Walked;
 
     let mut result = Vec::new();
-    walk_path_part2(&mut grid, (x, y), 1, &mut result);
+    walk_path_part2(&mut grid, start, 0, &mut result);
 
     *result.iter().max().unwrap()
 }
@@ -215,6 +153,20 @@ mod tests {
 #####################.#
 ";
 
+    #[test]
+    fn test_open_edges() {
+        // paths leading off the edge of the grid, and a loop right at the start
+        let input = "#.###
+.....
+#.#.#
+#...#
+###.#
+";
+
+        assert_eq!(6, part1(input));
+        assert_eq!(6, part2(input));
+    }
+
     #[test]
     fn test_part1() {
         let expected = 94;
@@ -0,0 +1,102 @@
+// area and lattice point counting for simple polygons on the integer grid,
+// with `y` growing downwards like the positions of a `Grid`
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+        }
+    }
+}
+
+// the corners visited by following the steps from the origin,
+// the path is expected to end where it started, so the start is only included once
+pub fn trace<I>(steps: I) -> Vec<(isize, isize)>
+where
+    I: IntoIterator<Item = (Direction, usize)>,
+{
+    let mut result = vec![(0, 0)];
+
+    let (mut x, mut y) = (0isize, 0isize);
+    for (direction, length) in steps {
+        let (dx, dy) = direction.delta();
+        x += dx * length as isize;
+        y += dy * length as isize;
+        result.push((x, y));
+    }
+
+    if result.len() > 1 && result.last() == result.first() {
+        result.pop();
+    }
+
+    result
+}
+
+// for a closed path, this is the number of lattice points on its boundary
+pub fn path_length<I>(steps: I) -> usize
+where
+    I: IntoIterator<Item = (Direction, usize)>,
+{
+    steps.into_iter().map(|(_, length)| length).sum()
+}
+
+// twice the area, which unlike the area itself is always an integer
+// https://en.wikipedia.org/wiki/Shoelace_formula
+pub fn double_area(vertices: &[(isize, isize)]) -> isize {
+    let n = vertices.len();
+
+    let sum: isize = (0..n)
+        .map(|i| {
+            let (x1, y1) = vertices[i];
+            let (x2, y2) = vertices[(i + 1) % n];
+            x1 * y2 - x2 * y1
+        })
+        .sum();
+
+    sum.abs()
+}
+
+// a multiple of 0.5, use `double_area` to stay in integers
+pub fn area(vertices: &[(isize, isize)]) -> f64 {
+    double_area(vertices) as f64 / 2.0
+}
+
+// the same as `path_length` for rectilinear polygons
+pub fn boundary_points(vertices: &[(isize, isize)]) -> isize {
+    let n = vertices.len();
+
+    (0..n)
+        .map(|i| {
+            let (x1, y1) = vertices[i];
+            let (x2, y2) = vertices[(i + 1) % n];
+            gcd((x2 - x1).abs(), (y2 - y1).abs())
+        })
+        .sum()
+}
+
+// the lattice points strictly inside, using Pick's theorem
+// A = I + B/2 - 1  =>  2I = 2A - B + 2
+pub fn interior_points(vertices: &[(isize, isize)]) -> isize {
+    (double_area(vertices) - boundary_points(vertices) + 2) / 2
+}
+
+fn gcd(mut a: isize, mut b: isize) -> isize {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
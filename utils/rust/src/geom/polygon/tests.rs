@@ -0,0 +1,70 @@
+use super::*;
+
+#[test]
+fn square() {
+    let steps = [
+        (Direction::Right, 3),
+        (Direction::Down, 3),
+        (Direction::Left, 3),
+        (Direction::Up, 3),
+    ];
+
+    let vertices = trace(steps);
+
+    assert_eq!(vertices, vec![(0, 0), (3, 0), (3, 3), (0, 3)]);
+    assert_eq!(area(&vertices), 9.0);
+    assert_eq!(boundary_points(&vertices), 12);
+    assert_eq!(path_length(steps), 12);
+    assert_eq!(interior_points(&vertices), 4);
+}
+
+#[test]
+fn orientation_does_not_matter() {
+    let clockwise = [(0, 0), (4, 0), (4, 2), (0, 2)];
+    let counter_clockwise = [(0, 0), (0, 2), (4, 2), (4, 0)];
+
+    assert_eq!(double_area(&clockwise), 16);
+    assert_eq!(double_area(&counter_clockwise), 16);
+}
+
+#[test]
+fn triangle() {
+    // boundary: 4 + 4 + gcd(4, 4) = 12, area: 8
+    let vertices = [(0, 0), (4, 0), (0, 4)];
+
+    assert_eq!(double_area(&vertices), 16);
+    assert_eq!(boundary_points(&vertices), 12);
+    assert_eq!(interior_points(&vertices), 3);
+}
+
+#[test]
+fn half_integer_area() {
+    let vertices = [(0, 0), (3, 0), (0, 3)];
+
+    assert_eq!(double_area(&vertices), 9);
+    assert_eq!(area(&vertices), 4.5);
+    assert_eq!(interior_points(&vertices), 1);
+}
+
+#[test]
+fn concave() {
+    // ###
+    // #.#
+    // #.###
+    // #...#
+    // #####
+    let steps = [
+        (Direction::Right, 2),
+        (Direction::Down, 2),
+        (Direction::Right, 2),
+        (Direction::Down, 2),
+        (Direction::Left, 4),
+        (Direction::Up, 4),
+    ];
+
+    let vertices = trace(steps);
+
+    assert_eq!(interior_points(&vertices), 5);
+    assert_eq!(boundary_points(&vertices), 16);
+    assert_eq!(path_length(steps), 16);
+}
@@ -1,5 +1,19 @@
 use std::error::Error;
+use std::fmt::Display;
+use std::iter::StepBy;
+use std::marker::PhantomData;
 use std::ops::{Index, IndexMut};
+use std::slice;
+
+mod parse;
+mod search;
+mod transform;
+mod view;
+
+pub use parse::{BlockError, GridParseError};
+pub use search::Components;
+pub use transform::{Transform, Transformed};
+pub use view::{GridView, PasteError, Rect};
 
 #[cfg(test)]
 mod tests;
@@ -16,7 +30,7 @@ impl<T: TryFrom<char>> TryFrom<&str> for Grid<T>
 where
     <T as TryFrom<char>>::Error: Into<Box<dyn Error>>,
 {
-    type Error = Box<dyn Error>;
+    type Error = GridParseError;
 
     fn try_from(input: &str) -> Result<Self, Self::Error> {
         Self::try_from_with(input, |c| T::try_from(c).map_err(|e| e.into()))
@@ -24,7 +38,7 @@ where
 }
 
 impl Grid<usize> {
-    pub fn try_from_usize(input: &str) -> Result<Self, Box<dyn Error>> {
+    pub fn try_from_usize(input: &str) -> Result<Self, GridParseError> {
         Self::try_from_with(input, |c| {
             let digit = c.to_digit(10).ok_or("not a digit")?;
             let val = usize::try_from(digit)?;
@@ -34,37 +48,29 @@ impl Grid<usize> {
 }
 
 impl<T> Grid<T> {
-    pub fn try_from_with<F>(input: &str, try_from: F) -> Result<Self, Box<dyn Error>>
+    pub fn try_from_with<F>(input: &str, try_from: F) -> Result<Self, GridParseError>
     where
         F: Fn(char) -> Result<T, Box<dyn Error>>,
     {
-        let trimmed = input.trim();
-
-        let rows = trimmed.lines().count();
-
-        let first_line = trimmed.lines().next().ok_or("empty input")?;
-        let columns = first_line.len();
-
-        let mut grid = Vec::with_capacity(rows * columns);
+        let lines: Vec<&str> = input.trim().lines().collect();
 
-        for line in trimmed.lines() {
-            if line.len() != columns {
-                return Err("not a grid".into());
-            }
+        Self::from_lines(&lines, &try_from)
+    }
 
-            let line = line
-                .chars()
-                .map(|c| try_from(c))
-                .collect::<Result<Vec<_>, _>>()?;
+    // the inverse of `try_from_with`, one line per row,
+    // so parsing the result with the inverse of `render` gives back the same grid
+    pub fn render_with<F>(&self, render: F) -> String
+    where
+        F: Fn(&T) -> char,
+    {
+        let mut result = String::with_capacity(self.rows * (self.columns + 1));
 
-            grid.extend(line);
+        for row in self.lines() {
+            result.extend(row.iter().map(&render));
+            result.push('\n');
         }
 
-        Ok(Grid {
-            grid,
-            rows,
-            columns,
-        })
+        result
     }
 
     pub fn new(grid: Vec<T>, rows: usize, columns: usize) -> Self {
@@ -77,7 +83,7 @@ impl<T> Grid<T> {
         }
     }
 
-    pub fn lines(&self) -> LinesIterator<T> {
+    pub fn lines(&self) -> LinesIterator<'_, T> {
         LinesIterator {
             grid: self,
             current_row: 0,
@@ -110,11 +116,60 @@ impl<T> Grid<T> {
             .get_mut(row * self.columns..row * self.columns + self.columns)
     }
 
+    // the tiles of column x from top to bottom
+    pub fn column(&self, x: usize) -> Option<Column<'_, T>> {
+        if x >= self.columns {
+            return None;
+        }
+        Some(self.grid.get(x..)?.iter().step_by(self.columns))
+    }
+
+    pub fn column_mut(&mut self, x: usize) -> Option<ColumnMut<'_, T>> {
+        if x >= self.columns {
+            return None;
+        }
+        Some(self.grid.get_mut(x..)?.iter_mut().step_by(self.columns))
+    }
+
+    pub fn columns_iter(&self) -> ColumnsIterator<'_, T> {
+        ColumnsIterator {
+            grid: self,
+            current_column: 0,
+        }
+    }
+
+    // every column as a strided iterator over the tiles,
+    // which unlike those of `column_mut` can all be borrowed at once
+    pub fn columns_iter_mut(&mut self) -> impl Iterator<Item = ColumnIterMut<'_, T>> {
+        let (rows, columns) = (self.rows, self.columns);
+        let start = self.grid.as_mut_ptr();
+
+        (0..columns).map(move |x| ColumnIterMut {
+            next: start.wrapping_add(x),
+            remaining: rows,
+            stride: columns,
+            marker: PhantomData,
+        })
+    }
+
+    // whether (x, y) is within the grid
+    pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+        x < self.columns && y < self.rows
+    }
+
+    // None outside of the grid, also right of the last column,
+    // which would otherwise wrap around into the next row
     pub fn get(&self, (x, y): (usize, usize)) -> Option<&T> {
+        if !self.contains((x, y)) {
+            return None;
+        }
         self.grid.get(y * self.columns + x)
     }
 
     pub fn get_mut(&mut self, (x, y): (usize, usize)) -> Option<&mut T> {
+        if !self.contains((x, y)) {
+            return None;
+        }
         self.grid.get_mut(y * self.columns + x)
     }
 
@@ -138,17 +193,63 @@ impl<T> Grid<T> {
 
     pub fn get_isize(&self, (x, y): (isize, isize)) -> Option<&T> {
         match (usize::try_from(x), usize::try_from(y)) {
-            (Ok(x), Ok(y)) => self.grid.get(y * self.columns + x),
+            (Ok(x), Ok(y)) => self.get((x, y)),
             _ => None,
         }
     }
 
     pub fn get_mut_isize(&mut self, (x, y): (isize, isize)) -> Option<&mut T> {
         match (usize::try_from(x), usize::try_from(y)) {
-            (Ok(x), Ok(y)) => self.grid.get_mut(y * self.columns + x),
+            (Ok(x), Ok(y)) => self.get_mut((x, y)),
             _ => None,
         }
     }
+
+    // treats everything outside of the grid as `default`
+    pub fn get_or<'a>(&'a self, position: (isize, isize), default: &'a T) -> &'a T {
+        self.get_isize(position).unwrap_or(default)
+    }
+
+    // treats the grid as repeating infinitely in every direction,
+    // None only if the grid is empty
+    pub fn get_wrapping(&self, (x, y): (isize, isize)) -> Option<&T> {
+        if self.grid.is_empty() {
+            return None;
+        }
+
+        let x = x.rem_euclid(self.columns as isize) as usize;
+        let y = y.rem_euclid(self.rows as isize) as usize;
+        Some(&self[(x, y)])
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    // surrounded by a border `n` tiles wide, so every position of the original grid moves by (n, n)
+    pub fn padded(&self, n: usize, fill: T) -> Grid<T> {
+        let (rows, columns) = (self.rows + 2 * n, self.columns + 2 * n);
+
+        let mut result = Grid::new(vec![fill; rows * columns], rows, columns);
+        result
+            .paste((n, n), self)
+            .expect("the padded grid is larger");
+
+        result
+    }
+}
+
+// one line per row, every tile should display as a single char
+// for the output to be parsable again
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.lines() {
+            for tile in row {
+                write!(f, "{}", tile)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
 }
 
 impl<T> Index<usize> for Grid<T> {
@@ -217,6 +318,60 @@ impl<T> IntoIterator for Grid<T> {
     }
 }
 
+pub type Column<'a, T> = StepBy<slice::Iter<'a, T>>;
+
+pub type ColumnMut<'a, T> = StepBy<slice::IterMut<'a, T>>;
+
+// the tiles of one column from top to bottom, see `Grid::columns_iter_mut`
+#[derive(Debug)]
+pub struct ColumnIterMut<'a, T> {
+    next: *mut T,
+    remaining: usize,
+    stride: usize,
+    marker: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> Iterator for ColumnIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let tile = self.next;
+        self.remaining -= 1;
+        self.next = self.next.wrapping_add(self.stride);
+
+        // SAFETY: `tile` is within the grid, which is mutably borrowed for 'a,
+        // and every column only visits the indices congruent to its own x modulo `stride`,
+        // so no tile is ever handed out twice
+        Some(unsafe { &mut *tile })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for ColumnIterMut<'_, T> {}
+
+#[derive(Debug, Clone)]
+pub struct ColumnsIterator<'a, T> {
+    grid: &'a Grid<T>,
+    current_column: usize,
+}
+
+impl<'a, T> Iterator for ColumnsIterator<'a, T> {
+    type Item = Column<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let c = self.grid.column(self.current_column)?;
+        self.current_column += 1;
+        Some(c)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LinesIterator<'a, T> {
     grid: &'a Grid<T>,
@@ -230,7 +385,7 @@ impl<'a, T> Iterator for LinesIterator<'a, T> {
         if self.current_row < self.grid.rows {
             let r = &self.grid[self.current_row];
             self.current_row += 1;
-            return Some(r);
+            Some(r)
         } else {
             None
         }
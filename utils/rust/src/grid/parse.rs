@@ -0,0 +1,175 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use super::Grid;
+
+// Rows and columns count from 0 like grid positions, and columns are counted in chars.
+#[derive(Debug)]
+pub enum GridParseError {
+    Empty,
+    Ragged {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    Tile {
+        row: usize,
+        column: usize,
+        found: char,
+        source: Box<dyn Error>,
+    },
+}
+
+impl GridParseError {
+    pub fn row(&self) -> usize {
+        match self {
+            GridParseError::Empty => 0,
+            GridParseError::Ragged { row, .. } | GridParseError::Tile { row, .. } => *row,
+        }
+    }
+}
+
+impl Display for GridParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridParseError::Empty => write!(f, "empty input"),
+            GridParseError::Ragged {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "not a grid, row {} has {} columns instead of {}",
+                row, found, expected
+            ),
+            GridParseError::Tile {
+                row,
+                column,
+                found,
+                source,
+            } => write!(
+                f,
+                "unexpected {:?} in row {}, column {}: {}",
+                found, row, column, source
+            ),
+        }
+    }
+}
+
+impl Error for GridParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GridParseError::Tile { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+// A failure to parse one of several blank line separated grids,
+// `block` and `line` count from 1 like in a text editor, `line` is within the whole input.
+#[derive(Debug)]
+pub struct BlockError {
+    pub block: usize,
+    pub line: usize,
+    pub source: GridParseError,
+}
+
+impl Display for BlockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "grid {} (line {}): {}",
+            self.block, self.line, self.source
+        )
+    }
+}
+
+impl Error for BlockError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl<T: TryFrom<char>> Grid<T>
+where
+    <T as TryFrom<char>>::Error: Into<Box<dyn Error>>,
+{
+    pub fn parse_many(input: &str) -> Result<Vec<Self>, BlockError> {
+        Self::parse_many_with(input, |c| T::try_from(c).map_err(|e| e.into()))
+    }
+}
+
+impl<T> Grid<T> {
+    // every block of lines separated by one or more blank lines as its own grid
+    pub fn parse_many_with<F>(input: &str, try_from: F) -> Result<Vec<Self>, BlockError>
+    where
+        F: Fn(char) -> Result<T, Box<dyn Error>>,
+    {
+        let mut blocks: Vec<(usize, Vec<&str>)> = Vec::new();
+        let mut previous_blank = true;
+
+        for (i, line) in input.lines().enumerate() {
+            let blank = line.trim().is_empty();
+
+            if !blank {
+                if previous_blank {
+                    blocks.push((i, Vec::new()));
+                }
+                blocks.last_mut().unwrap().1.push(line);
+            }
+
+            previous_blank = blank;
+        }
+
+        blocks
+            .into_iter()
+            .enumerate()
+            .map(|(block, (first_line, lines))| {
+                Self::from_lines(&lines, &try_from).map_err(|source| BlockError {
+                    block: block + 1,
+                    line: first_line + source.row() + 1,
+                    source,
+                })
+            })
+            .collect()
+    }
+
+    pub(super) fn from_lines<F>(lines: &[&str], try_from: &F) -> Result<Self, GridParseError>
+    where
+        F: Fn(char) -> Result<T, Box<dyn Error>>,
+    {
+        let rows = lines.len();
+
+        let first_line = lines.first().ok_or(GridParseError::Empty)?;
+        let columns = first_line.chars().count();
+
+        let mut grid = Vec::with_capacity(rows * columns);
+
+        for (row, line) in lines.iter().enumerate() {
+            let found = line.chars().count();
+            if found != columns {
+                return Err(GridParseError::Ragged {
+                    row,
+                    expected: columns,
+                    found,
+                });
+            }
+
+            for (column, c) in line.chars().enumerate() {
+                let tile = try_from(c).map_err(|source| GridParseError::Tile {
+                    row,
+                    column,
+                    found: c,
+                    source,
+                })?;
+                grid.push(tile);
+            }
+        }
+
+        Ok(Grid {
+            grid,
+            rows,
+            columns,
+        })
+    }
+}
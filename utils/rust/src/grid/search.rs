@@ -0,0 +1,147 @@
+use std::collections::{HashSet, VecDeque};
+
+use super::Grid;
+
+// The result of `Grid::label_components`, every passable position has the label of its component,
+// which is also the index of its size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Components {
+    pub labels: Grid<Option<usize>>,
+    pub sizes: Vec<usize>,
+}
+
+impl<T> Grid<T> {
+    // every tile together with its position, row by row
+    pub fn enumerate(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let columns = self.columns;
+        self.grid
+            .iter()
+            .enumerate()
+            .map(move |(i, tile)| ((i % columns, i / columns), tile))
+    }
+
+    // the first position row by row whose tile matches `predicate`
+    pub fn position<P>(&self, predicate: P) -> Option<(usize, usize)>
+    where
+        P: Fn(&T) -> bool,
+    {
+        self.enumerate()
+            .find(|(_, tile)| predicate(tile))
+            .map(|(position, _)| position)
+    }
+
+    // every position whose tile matches `predicate`, row by row
+    pub fn positions<'a, P>(&'a self, predicate: P) -> impl Iterator<Item = (usize, usize)> + 'a
+    where
+        P: Fn(&T) -> bool + 'a,
+    {
+        self.enumerate()
+            .filter(move |(_, tile)| predicate(tile))
+            .map(|(position, _)| position)
+    }
+
+    // the positions left, right, above and below that are still within the grid
+    pub fn neighbours(&self, (x, y): (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        let (rows, columns) = (self.rows, self.columns);
+
+        [
+            x.checked_sub(1).map(|x| (x, y)),
+            (x + 1 < columns).then_some((x + 1, y)),
+            y.checked_sub(1).map(|y| (x, y)),
+            (y + 1 < rows).then_some((x, y + 1)),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    // every position reachable from `start` by moving left, right, up or down
+    // over passable tiles, empty if `start` itself is not passable
+    pub fn flood_fill<F>(&self, start: (usize, usize), passable: F) -> HashSet<(usize, usize)>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut result = HashSet::new();
+
+        if !self.get(start).is_some_and(&passable) {
+            return result;
+        }
+
+        result.insert(start);
+        let mut stack = vec![start];
+
+        while let Some(position) = stack.pop() {
+            for next in self.neighbours(position) {
+                if passable(&self[next]) && result.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+
+        result
+    }
+
+    // splits the passable tiles into regions that are connected left, right, up or down,
+    // labelled in the order their top left-most tile appears
+    pub fn label_components<F>(&self, passable: F) -> Components
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut labels = Grid::new(vec![None; self.grid.len()], self.rows, self.columns);
+        let mut sizes = Vec::new();
+
+        for y in 0..self.rows {
+            for x in 0..self.columns {
+                if labels[(x, y)].is_some() || !passable(&self[(x, y)]) {
+                    continue;
+                }
+
+                let component = self.flood_fill((x, y), &passable);
+                for &position in &component {
+                    labels[position] = Some(sizes.len());
+                }
+                sizes.push(component.len());
+            }
+        }
+
+        Components { labels, sizes }
+    }
+
+    // the fewest steps from `start` to every position, moving along `neighbours` over passable
+    // tiles, None for positions that can't be reached
+    //
+    // `neighbours` is usually `|p| grid.neighbours(p)`, but can e.g. also allow diagonal steps
+    pub fn distance_map<F, N, I>(
+        &self,
+        start: (usize, usize),
+        passable: F,
+        neighbours: N,
+    ) -> Grid<Option<u32>>
+    where
+        F: Fn(&T) -> bool,
+        N: Fn((usize, usize)) -> I,
+        I: IntoIterator<Item = (usize, usize)>,
+    {
+        let mut distances = Grid::new(vec![None; self.grid.len()], self.rows, self.columns);
+
+        if !self.get(start).is_some_and(&passable) {
+            return distances;
+        }
+
+        distances[start] = Some(0);
+        let mut queue = VecDeque::from([(start, 0)]);
+
+        while let Some((position, distance)) = queue.pop_front() {
+            for next in neighbours(position) {
+                let Some(tile) = self.get(next) else {
+                    continue;
+                };
+                if distances[next].is_none() && passable(tile) {
+                    distances[next] = Some(distance + 1);
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+
+        distances
+    }
+}
@@ -1,4 +1,6 @@
 use super::*;
+use std::collections::HashSet;
+use std::error::Error;
 
 #[test]
 fn construct_grid() {
@@ -87,3 +89,552 @@ fn construct_grid() {
 
     assert_eq!(grid.grid, expected);
 }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mirror {
+    Empty,
+    Slash,
+    Backslash,
+}
+
+impl TryFrom<char> for Mirror {
+    type Error = String;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            '.' => Ok(Self::Empty),
+            '/' => Ok(Self::Slash),
+            '\\' => Ok(Self::Backslash),
+            _ => Err("unknown tile".to_string()),
+        }
+    }
+}
+
+impl Display for Mirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "."),
+            Self::Slash => write!(f, "/"),
+            Self::Backslash => write!(f, "\\"),
+        }
+    }
+}
+
+const MIRRORS: &str = r"..\./
+./..\
+\/...
+";
+
+#[test]
+fn display_round_trip() {
+    let grid: Grid<Mirror> = Grid::try_from(MIRRORS).unwrap();
+
+    assert_eq!(MIRRORS, grid.to_string());
+    assert_eq!(grid, Grid::try_from(grid.to_string().as_str()).unwrap());
+}
+
+#[test]
+fn render_with_round_trip() {
+    let grid = Grid::try_from_with(MIRRORS, |c| Ok(c == '.')).unwrap();
+
+    let rendered = grid.render_with(|&empty| if empty { '.' } else { '#' });
+    assert_eq!("..#.#\n.#..#\n##...\n", rendered);
+
+    let parsed = Grid::try_from_with(&rendered, |c| Ok(c == '.')).unwrap();
+    assert_eq!(grid, parsed);
+}
+
+#[test]
+fn digits_round_trip() {
+    let input = "0123\n4567\n8901\n";
+    let grid = Grid::try_from_usize(input).unwrap();
+
+    let rendered = grid.render_with(|&d| char::from_digit(d as u32, 10).unwrap());
+    assert_eq!(input, rendered);
+    assert_eq!(input, grid.to_string());
+}
+
+#[test]
+fn get_out_of_bounds() {
+    let grid = Grid::try_from_usize("12\n34\n").unwrap();
+
+    assert_eq!(Some(&2), grid.get((1, 0)));
+    assert_eq!(None, grid.get((2, 0)));
+    assert_eq!(None, grid.get((0, 2)));
+    assert_eq!(None, grid.get_isize((-1, 1)));
+    assert_eq!(None, grid.get_isize((2, 0)));
+
+    assert!(grid.contains((1, 1)));
+    assert!(!grid.contains((2, 0)));
+    assert!(!grid.contains((0, 2)));
+}
+
+// 3 columns, 2 rows, so that swapped axes can't go unnoticed
+//
+// 123
+// 456
+fn numbers() -> Grid<usize> {
+    Grid::try_from_usize("123\n456\n").unwrap()
+}
+
+#[test]
+fn transforms() {
+    let grid = numbers();
+
+    let cases = [
+        (Transform::Identity, "123\n456\n"),
+        (Transform::RotateCw, "41\n52\n63\n"),
+        (Transform::Rotate180, "654\n321\n"),
+        (Transform::RotateCcw, "36\n25\n14\n"),
+        (Transform::FlipH, "321\n654\n"),
+        (Transform::FlipV, "456\n123\n"),
+        (Transform::Transpose, "14\n25\n36\n"),
+        (Transform::AntiTranspose, "63\n52\n41\n"),
+    ];
+
+    for (transform, expected) in cases {
+        assert_eq!(
+            expected,
+            grid.transform(transform).to_string(),
+            "{:?}",
+            transform
+        );
+    }
+
+    assert_eq!(grid.transpose(), grid.transform(Transform::Transpose));
+    assert_eq!(grid.rotate_cw(), grid.transform(Transform::RotateCw));
+    assert_eq!(grid.rotate_ccw(), grid.transform(Transform::RotateCcw));
+    assert_eq!(grid.flip_h(), grid.transform(Transform::FlipH));
+    assert_eq!(grid.flip_v(), grid.transform(Transform::FlipV));
+}
+
+#[test]
+fn transforms_compose() {
+    let grid = numbers();
+
+    for a in Transform::ALL {
+        for b in Transform::ALL {
+            let expected = grid.transform(a).transform(b);
+
+            assert_eq!(expected, grid.transform(a.then(b)), "{:?} then {:?}", a, b);
+            assert_eq!(
+                expected,
+                grid.transformed(a).transformed(b).to_grid(),
+                "{:?} then {:?}",
+                a,
+                b
+            );
+        }
+
+        assert_eq!(grid, grid.transform(a).transform(a.inverse()), "{:?}", a);
+    }
+}
+
+#[test]
+fn transform_identities() {
+    let grid = numbers();
+    let view = grid.transformed(Transform::Identity);
+
+    let four_times = view.rotate_cw().rotate_cw().rotate_cw().rotate_cw();
+    assert_eq!(Transform::Identity, four_times.transform());
+
+    assert_eq!(Transform::RotateCw, view.transpose().flip_h().transform());
+    assert_eq!(Transform::RotateCcw, view.transpose().flip_v().transform());
+    assert_eq!(Transform::Rotate180, view.flip_h().flip_v().transform());
+    assert_eq!(
+        Transform::AntiTranspose,
+        view.rotate_cw().flip_v().transform()
+    );
+}
+
+#[test]
+fn transformed_view() {
+    let grid = numbers();
+    let view = grid.transformed(Transform::RotateCw);
+
+    assert_eq!((3, 2), (view.rows(), view.columns()));
+    assert_eq!(4, view[(0, 0)]);
+    assert_eq!(3, view[(1, 2)]);
+    assert_eq!(None, view.get((2, 0)));
+    assert_eq!(None, view.get((0, 3)));
+    assert_eq!(
+        vec![4, 1, 5, 2, 6, 3],
+        view.iter().copied().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn columns() {
+    let grid = numbers();
+
+    assert_eq!(vec![&2, &5], grid.column(1).unwrap().collect::<Vec<_>>());
+    assert_eq!(
+        vec![&6, &3],
+        grid.column(2).unwrap().rev().collect::<Vec<_>>()
+    );
+    assert!(grid.column(3).is_none());
+
+    let columns: Vec<Vec<usize>> = grid.columns_iter().map(|c| c.copied().collect()).collect();
+    assert_eq!(vec![vec![1, 4], vec![2, 5], vec![3, 6]], columns);
+
+    // columns of a grid are the rows of its transpose
+    let transposed = grid.transpose();
+    for (column, row) in grid.columns_iter().zip(transposed.lines()) {
+        assert!(column.eq(row.iter()));
+    }
+}
+
+#[test]
+fn columns_mut() {
+    let mut grid = numbers();
+
+    for tile in grid.column_mut(0).unwrap() {
+        *tile *= 10;
+    }
+    assert!(grid.column_mut(3).is_none());
+    assert_eq!(10, grid[(0usize, 0usize)]);
+    assert_eq!(40, grid[(0usize, 1usize)]);
+
+    for (x, column) in grid.columns_iter_mut().enumerate() {
+        for tile in column {
+            *tile += x;
+        }
+    }
+    assert_eq!(vec![10, 3, 5, 40, 6, 8], *grid.grid());
+
+    // every column borrowed at the same time
+    let mut columns: Vec<_> = grid.columns_iter_mut().collect();
+    assert!(columns.iter().all(|column| column.len() == 2));
+    for column in &mut columns[1..] {
+        *column.next().unwrap() = 0;
+    }
+    assert_eq!(vec![10, 0, 0, 40, 6, 8], *grid.grid());
+}
+
+#[test]
+fn view() {
+    let grid = Grid::try_from_usize("1234\n5678\n9012\n").unwrap();
+
+    let view = grid.view(Rect::new((1, 1), 2, 2)).unwrap();
+    assert_eq!((2, 2), (view.rows(), view.columns()));
+    assert_eq!(6, view[(0, 0)]);
+    assert_eq!(1, view[(1, 1)]);
+    assert_eq!([6, 7], view[0]);
+    assert_eq!(None, view.get((2, 0)));
+    assert_eq!(None, view.get((0, 2)));
+    assert_eq!(vec![6, 7, 0, 1], view.iter().copied().collect::<Vec<_>>());
+    assert_eq!("67\n01\n", view.to_string());
+    assert_eq!(Grid::try_from_usize("67\n01\n").unwrap(), view.to_owned());
+
+    let row = grid.view(Rect::new((0, 2), 1, 3)).unwrap();
+    assert_eq!((1, 3), (row.rows(), row.columns()));
+    assert_eq!("901\n", row.to_string());
+
+    assert!(grid.view(Rect::new((0, 0), 3, 4)).is_some());
+    assert!(grid.view(Rect::new((1, 0), 1, 4)).is_none());
+    assert!(grid.view(Rect::new((0, 2), 2, 1)).is_none());
+}
+
+#[test]
+fn paste() {
+    let mut grid = Grid::new(vec![0; 12], 3, 4);
+    let small = Grid::try_from_usize("12\n34\n").unwrap();
+
+    grid.paste((2, 1), &small).unwrap();
+    grid.paste((0, 0), small.view(Rect::new((0, 1), 1, 2)).unwrap())
+        .unwrap();
+
+    assert_eq!("3400\n0012\n0034\n", grid.to_string());
+    assert_eq!(
+        small,
+        grid.view(Rect::new((2, 1), 2, 2)).unwrap().to_owned()
+    );
+}
+
+#[test]
+fn paste_out_of_bounds() {
+    let mut grid = Grid::new(vec![0; 4], 2, 2);
+
+    let error = grid
+        .paste((1, 0), &Grid::new(vec![1; 2], 1, 2))
+        .unwrap_err();
+    assert_eq!(
+        "a 1x2 grid does not fit into a 2x2 grid at (1, 0)",
+        error.to_string()
+    );
+    assert!(grid.paste((0, 1), &Grid::new(vec![1; 2], 2, 1)).is_err());
+    assert_eq!(vec![0; 4], *grid.grid());
+}
+
+// 1 is passable, 0 is a wall
+const REGIONS: &str = "11011
+10010
+00111
+11000
+";
+
+#[test]
+fn neighbours() {
+    let grid = Grid::try_from_usize(REGIONS).unwrap();
+
+    let sorted = |position| {
+        let mut result: Vec<_> = grid.neighbours(position).collect();
+        result.sort();
+        result
+    };
+
+    assert_eq!(vec![(0, 1), (1, 0)], sorted((0, 0)));
+    assert_eq!(vec![(1, 1), (2, 0), (2, 2), (3, 1)], sorted((2, 1)));
+    assert_eq!(vec![(3, 3), (4, 2)], sorted((4, 3)));
+}
+
+#[test]
+fn flood_fill() {
+    let grid = Grid::try_from_usize(REGIONS).unwrap();
+
+    let expected = HashSet::from([(3, 0), (4, 0), (3, 1), (2, 2), (3, 2), (4, 2)]);
+    assert_eq!(expected, grid.flood_fill((2, 2), |&t| t == 1));
+
+    assert_eq!(
+        HashSet::from([(0, 3), (1, 3)]),
+        grid.flood_fill((1, 3), |&t| t == 1)
+    );
+    assert!(grid.flood_fill((2, 0), |&t| t == 1).is_empty());
+    assert!(grid.flood_fill((5, 0), |&t| t == 1).is_empty());
+}
+
+#[test]
+fn label_components() {
+    let grid = Grid::try_from_usize(REGIONS).unwrap();
+
+    let components = grid.label_components(|&t| t == 1);
+    assert_eq!(vec![3, 6, 2], components.sizes);
+
+    let rendered = components.labels.render_with(|label| match label {
+        Some(l) => char::from_digit(*l as u32, 10).unwrap(),
+        None => '.',
+    });
+    assert_eq!("00.11\n0..1.\n..111\n22...\n", rendered);
+
+    let walls = grid.label_components(|&t| t == 0);
+    assert_eq!(vec![5, 1, 3], walls.sizes);
+}
+
+#[test]
+fn distance_map() {
+    let grid = Grid::try_from_usize(REGIONS).unwrap();
+
+    let distances = grid.distance_map((4, 0), |&t| t == 1, |p| grid.neighbours(p));
+    let rendered = distances.render_with(|d| match d {
+        Some(d) => char::from_digit(*d, 10).unwrap(),
+        None => '.',
+    });
+    assert_eq!("...10\n...2.\n..434\n.....\n", rendered);
+
+    let unreachable = grid.distance_map((2, 0), |&t| t == 1, |p| grid.neighbours(p));
+    assert!(unreachable.grid().iter().all(Option::is_none));
+}
+
+#[test]
+fn distance_map_diagonal() {
+    let grid = Grid::try_from_usize(REGIONS).unwrap();
+
+    // moving like a king, so the bottom left region can be reached through (2, 2) -> (1, 3)
+    let king = |(x, y): (usize, usize)| {
+        let mut result = Vec::new();
+        for dy in -1..=1isize {
+            for dx in -1..=1isize {
+                if let (Some(x), Some(y)) = (x.checked_add_signed(dx), y.checked_add_signed(dy)) {
+                    result.push((x, y));
+                }
+            }
+        }
+        result
+    };
+
+    let distances = grid.distance_map((4, 0), |&t| t == 1, king);
+    assert_eq!(Some(1), distances[(3usize, 1usize)]);
+    assert_eq!(Some(2), distances[(2usize, 2usize)]);
+    assert_eq!(Some(4), distances[(0usize, 3usize)]);
+    assert_eq!(None, distances[(1usize, 0usize)]);
+}
+
+const PATTERNS: &str = "#.##..##.
+..#.##.#.
+##......#
+
+#...##..#
+#....#..#
+
+
+..##..###
+";
+
+#[test]
+fn parse_many() {
+    let grids: Vec<Grid<bool>> = Grid::parse_many_with(PATTERNS, |c| Ok(c == '#')).unwrap();
+
+    assert_eq!(3, grids.len());
+    assert_eq!((3, 9), (grids[0].rows(), grids[0].columns()));
+    assert_eq!((2, 9), (grids[1].rows(), grids[1].columns()));
+    assert_eq!((1, 9), (grids[2].rows(), grids[2].columns()));
+    assert!(grids[1][(4usize, 0usize)] && !grids[1][(4usize, 1usize)]);
+
+    let mirrors: Vec<Grid<Mirror>> =
+        Grid::parse_many(&format!("\n\n{}\n\n{}", MIRRORS, MIRRORS)).unwrap();
+    assert_eq!(2, mirrors.len());
+    assert_eq!(MIRRORS, mirrors[1].to_string());
+
+    assert!(Grid::<Mirror>::parse_many("\n \n").unwrap().is_empty());
+}
+
+#[test]
+fn parse_many_errors() {
+    let error = Grid::<Mirror>::parse_many("..\n./\n\n\\.\n.#\n").unwrap_err();
+    assert_eq!((2, 5), (error.block, error.line));
+    assert_eq!(
+        "grid 2 (line 5): unexpected '#' in row 1, column 1: unknown tile",
+        error.to_string()
+    );
+
+    let error = Grid::<Mirror>::parse_many("..\n\n..\n...\n").unwrap_err();
+    assert_eq!((2, 4), (error.block, error.line));
+    assert_eq!(
+        "grid 2 (line 4): not a grid, row 1 has 3 columns instead of 2",
+        error.to_string()
+    );
+}
+
+#[test]
+fn parse_errors() {
+    assert!(matches!(
+        Grid::<Mirror>::try_from(" \n\n"),
+        Err(GridParseError::Empty)
+    ));
+
+    assert!(matches!(
+        Grid::<Mirror>::try_from("../\n./\n"),
+        Err(GridParseError::Ragged {
+            row: 1,
+            expected: 3,
+            found: 2
+        })
+    ));
+
+    let error = Grid::try_from_usize("123\n4x6\n").unwrap_err();
+    assert!(matches!(
+        error,
+        GridParseError::Tile {
+            row: 1,
+            column: 1,
+            found: 'x',
+            ..
+        }
+    ));
+    assert_eq!(
+        "unexpected 'x' in row 1, column 1: not a digit",
+        error.to_string()
+    );
+    assert_eq!("not a digit", error.source().unwrap().to_string());
+}
+
+#[test]
+fn parse_non_ascii() {
+    // the box-drawing chars are 3 bytes each
+    let grid = Grid::try_from_with("┌─┐\n│.│\n└─┘\n", Ok).unwrap();
+
+    assert_eq!((3, 3), (grid.rows(), grid.columns()));
+    assert_eq!('─', grid[(1usize, 0usize)]);
+    assert_eq!("┌─┐\n│.│\n└─┘\n", grid.to_string());
+
+    let error = Grid::try_from_with("┌─┐\n│.\n", Ok).unwrap_err();
+    assert!(matches!(
+        error,
+        GridParseError::Ragged {
+            row: 1,
+            expected: 3,
+            found: 2
+        }
+    ));
+}
+
+#[test]
+fn positions() {
+    let grid = Grid::try_from_usize(REGIONS).unwrap();
+
+    assert_eq!(Some((2, 0)), grid.position(|&t| t == 0));
+    assert_eq!(None, grid.position(|&t| t == 2));
+    assert_eq!(
+        vec![
+            (2, 0),
+            (1, 1),
+            (2, 1),
+            (4, 1),
+            (0, 2),
+            (1, 2),
+            (2, 3),
+            (3, 3),
+            (4, 3)
+        ],
+        grid.positions(|&t| t == 0).collect::<Vec<_>>()
+    );
+
+    let numbers = numbers();
+    let expected = vec![
+        ((0, 0), &1),
+        ((1, 0), &2),
+        ((2, 0), &3),
+        ((0, 1), &4),
+        ((1, 1), &5),
+        ((2, 1), &6),
+    ];
+    assert_eq!(expected, numbers.enumerate().collect::<Vec<_>>());
+
+    for (position, tile) in numbers.enumerate() {
+        assert_eq!(*tile, numbers[position]);
+    }
+}
+
+#[test]
+fn padded() {
+    let grid = numbers();
+
+    let padded = grid.padded(1, 0);
+    assert_eq!("00000\n01230\n04560\n00000\n", padded.to_string());
+    assert_eq!(
+        grid,
+        padded.view(Rect::new((1, 1), 2, 3)).unwrap().to_owned()
+    );
+
+    assert_eq!(grid, grid.padded(0, 0));
+    assert_eq!(
+        (6, 7),
+        (grid.padded(2, 9).rows(), grid.padded(2, 9).columns())
+    );
+}
+
+#[test]
+fn get_or() {
+    let grid = numbers();
+
+    assert_eq!(&1, grid.get_or((0, 0), &0));
+    assert_eq!(&6, grid.get_or((2, 1), &0));
+    assert_eq!(&0, grid.get_or((-1, 0), &0));
+    assert_eq!(&0, grid.get_or((0, -1), &0));
+    assert_eq!(&0, grid.get_or((3, 0), &0));
+    assert_eq!(&0, grid.get_or((0, 2), &0));
+}
+
+#[test]
+fn get_wrapping() {
+    let grid = numbers();
+
+    assert_eq!(Some(&1), grid.get_wrapping((0, 0)));
+    assert_eq!(Some(&3), grid.get_wrapping((-1, 0)));
+    assert_eq!(Some(&4), grid.get_wrapping((0, -1)));
+    assert_eq!(Some(&5), grid.get_wrapping((7, 3)));
+    assert_eq!(Some(&6), grid.get_wrapping((-4, -5)));
+
+    let empty: Grid<usize> = Grid::new(Vec::new(), 0, 0);
+    assert_eq!(None, empty.get_wrapping((0, 0)));
+}
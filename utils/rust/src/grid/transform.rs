@@ -0,0 +1,264 @@
+use std::ops::Index;
+
+use super::Grid;
+
+// The eight symmetries of a rectangle, i.e. every way to rotate and mirror a grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Transform {
+    Identity,
+    RotateCw,
+    Rotate180,
+    RotateCcw,
+    // mirrors left and right
+    FlipH,
+    // mirrors top and bottom
+    FlipV,
+    // mirrors along the diagonal from the top left to the bottom right
+    Transpose,
+    // mirrors along the diagonal from the top right to the bottom left
+    AntiTranspose,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::RotateCw,
+        Transform::Rotate180,
+        Transform::RotateCcw,
+        Transform::FlipH,
+        Transform::FlipV,
+        Transform::Transpose,
+        Transform::AntiTranspose,
+    ];
+
+    // Every transform maps a position of the result back to a position of the original grid by
+    // first mirroring x and/or y within the result, then swapping x and y.
+    //
+    // Measured from the center of the grid, that is the matrix `swap * diag(sx, sy)` with signs
+    // `sx` and `sy`, which makes composing transforms a matrix product.
+    fn matrix(self) -> [[i8; 2]; 2] {
+        let (swap, flip_x, flip_y) = self.parts();
+        let sx = if flip_x { -1 } else { 1 };
+        let sy = if flip_y { -1 } else { 1 };
+
+        if swap {
+            [[0, sy], [sx, 0]]
+        } else {
+            [[sx, 0], [0, sy]]
+        }
+    }
+
+    fn from_matrix(m: [[i8; 2]; 2]) -> Self {
+        if m[0][0] == 0 {
+            Self::from_parts(true, m[1][0] < 0, m[0][1] < 0)
+        } else {
+            Self::from_parts(false, m[0][0] < 0, m[1][1] < 0)
+        }
+    }
+
+    // (swap x and y, mirror x, mirror y)
+    fn parts(self) -> (bool, bool, bool) {
+        match self {
+            Transform::Identity => (false, false, false),
+            Transform::FlipH => (false, true, false),
+            Transform::FlipV => (false, false, true),
+            Transform::Rotate180 => (false, true, true),
+            Transform::Transpose => (true, false, false),
+            Transform::RotateCw => (true, true, false),
+            Transform::RotateCcw => (true, false, true),
+            Transform::AntiTranspose => (true, true, true),
+        }
+    }
+
+    fn from_parts(swap: bool, flip_x: bool, flip_y: bool) -> Self {
+        match (swap, flip_x, flip_y) {
+            (false, false, false) => Transform::Identity,
+            (false, true, false) => Transform::FlipH,
+            (false, false, true) => Transform::FlipV,
+            (false, true, true) => Transform::Rotate180,
+            (true, false, false) => Transform::Transpose,
+            (true, true, false) => Transform::RotateCw,
+            (true, false, true) => Transform::RotateCcw,
+            (true, true, true) => Transform::AntiTranspose,
+        }
+    }
+
+    // the transform that applies `self` first and `next` afterwards
+    pub fn then(self, next: Transform) -> Transform {
+        let (a, b) = (self.matrix(), next.matrix());
+
+        let mut m = [[0; 2]; 2];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = a[i][0] * b[0][j] + a[i][1] * b[1][j];
+            }
+        }
+
+        Self::from_matrix(m)
+    }
+
+    pub fn inverse(self) -> Transform {
+        match self {
+            Transform::RotateCw => Transform::RotateCcw,
+            Transform::RotateCcw => Transform::RotateCw,
+            other => other,
+        }
+    }
+
+    pub fn swaps_axes(self) -> bool {
+        self.parts().0
+    }
+
+    // the position in the original grid with `rows` and `columns`
+    // that ends up at `(x, y)` after transforming it
+    fn source(self, (x, y): (usize, usize), rows: usize, columns: usize) -> (usize, usize) {
+        let (swap, flip_x, flip_y) = self.parts();
+        let (width, height) = if swap {
+            (rows, columns)
+        } else {
+            (columns, rows)
+        };
+
+        let x = if flip_x { width - 1 - x } else { x };
+        let y = if flip_y { height - 1 - y } else { y };
+
+        if swap {
+            (y, x)
+        } else {
+            (x, y)
+        }
+    }
+}
+
+// A transformed grid that only remaps positions and never copies any tiles.
+#[derive(Debug)]
+pub struct Transformed<'a, T> {
+    grid: &'a Grid<T>,
+    transform: Transform,
+}
+
+// not derived, as that would require `T: Clone` and `T: Copy`
+impl<T> Clone for Transformed<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Transformed<'_, T> {}
+
+impl<'a, T> Transformed<'a, T> {
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    pub fn rows(&self) -> usize {
+        if self.transform.swaps_axes() {
+            self.grid.columns
+        } else {
+            self.grid.rows
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        if self.transform.swaps_axes() {
+            self.grid.rows
+        } else {
+            self.grid.columns
+        }
+    }
+
+    pub fn get(&self, (x, y): (usize, usize)) -> Option<&'a T> {
+        if x >= self.columns() || y >= self.rows() {
+            return None;
+        }
+
+        let source = self
+            .transform
+            .source((x, y), self.grid.rows, self.grid.columns);
+        self.grid.get(source)
+    }
+
+    // every tile, row by row
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> {
+        let view = *self;
+        (0..view.rows())
+            .flat_map(move |y| (0..view.columns()).map(move |x| view.get((x, y)).unwrap()))
+    }
+
+    pub fn transformed(&self, transform: Transform) -> Transformed<'a, T> {
+        Transformed {
+            grid: self.grid,
+            transform: self.transform.then(transform),
+        }
+    }
+
+    pub fn transpose(&self) -> Transformed<'a, T> {
+        self.transformed(Transform::Transpose)
+    }
+
+    pub fn rotate_cw(&self) -> Transformed<'a, T> {
+        self.transformed(Transform::RotateCw)
+    }
+
+    pub fn rotate_ccw(&self) -> Transformed<'a, T> {
+        self.transformed(Transform::RotateCcw)
+    }
+
+    pub fn flip_h(&self) -> Transformed<'a, T> {
+        self.transformed(Transform::FlipH)
+    }
+
+    pub fn flip_v(&self) -> Transformed<'a, T> {
+        self.transformed(Transform::FlipV)
+    }
+
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::new(self.iter().cloned().collect(), self.rows(), self.columns())
+    }
+}
+
+impl<T> Index<(usize, usize)> for Transformed<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: (usize, usize)) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn transformed(&self, transform: Transform) -> Transformed<'_, T> {
+        Transformed {
+            grid: self,
+            transform,
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn transform(&self, transform: Transform) -> Grid<T> {
+        self.transformed(transform).to_grid()
+    }
+
+    pub fn transpose(&self) -> Grid<T> {
+        self.transform(Transform::Transpose)
+    }
+
+    pub fn rotate_cw(&self) -> Grid<T> {
+        self.transform(Transform::RotateCw)
+    }
+
+    pub fn rotate_ccw(&self) -> Grid<T> {
+        self.transform(Transform::RotateCcw)
+    }
+
+    pub fn flip_h(&self) -> Grid<T> {
+        self.transform(Transform::FlipH)
+    }
+
+    pub fn flip_v(&self) -> Grid<T> {
+        self.transform(Transform::FlipV)
+    }
+}
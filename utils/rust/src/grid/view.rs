@@ -0,0 +1,190 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::ops::Index;
+
+use super::Grid;
+
+// A rectangle of positions, `rows` high and `columns` wide with its top left corner at (x, y).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub columns: usize,
+    pub rows: usize,
+}
+
+impl Rect {
+    // rows before columns, the same as `Grid::new`
+    pub const fn new((x, y): (usize, usize), rows: usize, columns: usize) -> Self {
+        Self {
+            x,
+            y,
+            columns,
+            rows,
+        }
+    }
+}
+
+// The error of `Grid::paste`, the sizes are (rows, columns).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasteError {
+    pub origin: (usize, usize),
+    pub size: (usize, usize),
+    pub target: (usize, usize),
+}
+
+impl Display for PasteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "a {}x{} grid does not fit into a {}x{} grid at ({}, {})",
+            self.size.0, self.size.1, self.target.0, self.target.1, self.origin.0, self.origin.1
+        )
+    }
+}
+
+impl Error for PasteError {}
+
+// A borrowed rectangle of a grid, positions are relative to the top left corner of the rectangle.
+#[derive(Debug)]
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    rect: Rect,
+}
+
+// not derived, as that would require `T: Clone` and `T: Copy`
+impl<T> Clone for GridView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for GridView<'_, T> {}
+
+impl<'a, T> GridView<'a, T> {
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rect.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.rect.columns
+    }
+
+    pub fn get_row(&self, row: usize) -> Option<&'a [T]> {
+        if row >= self.rect.rows {
+            return None;
+        }
+
+        let start = (self.rect.y + row) * self.grid.columns + self.rect.x;
+        self.grid.grid.get(start..start + self.rect.columns)
+    }
+
+    pub fn get(&self, (x, y): (usize, usize)) -> Option<&'a T> {
+        self.get_row(y)?.get(x)
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &'a [T]> {
+        let view = *self;
+        (0..view.rect.rows).map(move |row| view.get_row(row).unwrap())
+    }
+
+    // every tile, row by row
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> {
+        self.lines().flatten()
+    }
+
+    pub fn to_owned(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::new(
+            self.iter().cloned().collect(),
+            self.rect.rows,
+            self.rect.columns,
+        )
+    }
+}
+
+impl<T> Index<usize> for GridView<'_, T> {
+    type Output = [T];
+
+    fn index(&self, row: usize) -> &Self::Output {
+        self.get_row(row).unwrap()
+    }
+}
+
+impl<T> Index<(usize, usize)> for GridView<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: (usize, usize)) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+impl<T: Display> Display for GridView<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.lines() {
+            for tile in row {
+                write!(f, "{}", tile)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+// the whole grid
+impl<'a, T> From<&'a Grid<T>> for GridView<'a, T> {
+    fn from(grid: &'a Grid<T>) -> Self {
+        GridView {
+            grid,
+            rect: Rect::new((0, 0), grid.rows, grid.columns),
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    // None if the rectangle does not fit inside the grid
+    pub fn view(&self, rect: Rect) -> Option<GridView<'_, T>> {
+        if rect.x + rect.columns > self.columns || rect.y + rect.rows > self.rows {
+            return None;
+        }
+
+        Some(GridView { grid: self, rect })
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    // overwrites the tiles covered by `other` (a grid or a view of one)
+    // when its top left corner is placed at `origin`
+    //
+    // leaves the grid unchanged if `other` does not fit
+    pub fn paste<'b>(
+        &mut self,
+        (x, y): (usize, usize),
+        other: impl Into<GridView<'b, T>>,
+    ) -> Result<(), PasteError>
+    where
+        T: 'b,
+    {
+        let other = other.into();
+        if x + other.columns() > self.columns || y + other.rows() > self.rows {
+            return Err(PasteError {
+                origin: (x, y),
+                size: (other.rows(), other.columns()),
+                target: (self.rows, self.columns),
+            });
+        }
+
+        for (i, row) in other.lines().enumerate() {
+            self.get_row_mut(y + i).unwrap()[x..x + other.columns()].clone_from_slice(row);
+        }
+
+        Ok(())
+    }
+}
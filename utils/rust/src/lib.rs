@@ -1 +1,3 @@
+pub mod geom;
 pub mod grid;
+pub mod ranges;
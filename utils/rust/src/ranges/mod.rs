@@ -0,0 +1,294 @@
+use std::cmp::{max, min};
+use std::ops::{Index, IndexMut};
+
+#[cfg(test)]
+mod tests;
+
+// the integers from `start` up to but excluding `end`, empty if `start >= end`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Interval {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Interval {
+    pub const fn new(start: i64, end: i64) -> Self {
+        Self { start, end }
+    }
+
+    // including `end`, except for `i64::MAX` which can't be included
+    pub const fn inclusive(start: i64, end: i64) -> Self {
+        Self::new(start, end.saturating_add(1))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    pub fn len(&self) -> u64 {
+        if self.is_empty() {
+            0
+        } else {
+            (self.end - self.start) as u64
+        }
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self::new(max(self.start, other.start), min(self.end, other.end))
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    // everything in `self` that is not in `other`
+    pub fn difference(&self, other: &Self) -> IntervalSet {
+        let mut result = IntervalSet::from(*self);
+        result.remove(*other);
+        result
+    }
+
+    // splits into the values `< at` and the values `>= at`
+    pub fn split_at(&self, at: i64) -> (Self, Self) {
+        let at = at.clamp(self.start, max(self.start, self.end));
+        (Self::new(self.start, at), Self::new(at, self.end))
+    }
+}
+
+// A set of integers stored as sorted, disjoint and non-adjacent intervals.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl From<Interval> for IntervalSet {
+    fn from(interval: Interval) -> Self {
+        let mut result = Self::new();
+        result.insert(interval);
+        result
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> Self {
+        let mut result = Self::new();
+        for interval in iter {
+            result.insert(interval);
+        }
+        result
+    }
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Interval> {
+        self.intervals.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    // the number of integers in the set
+    pub fn len(&self) -> u64 {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        let i = self.intervals.partition_point(|i| i.end <= value);
+        self.intervals.get(i).is_some_and(|i| i.contains(value))
+    }
+
+    pub fn insert(&mut self, interval: Interval) {
+        if interval.is_empty() {
+            return;
+        }
+
+        // every interval touching or overlapping the new one gets merged into it
+        let first = self.intervals.partition_point(|i| i.end < interval.start);
+        let last = self.intervals.partition_point(|i| i.start <= interval.end);
+
+        let mut merged = interval;
+        if first < last {
+            merged.start = min(merged.start, self.intervals[first].start);
+            merged.end = max(merged.end, self.intervals[last - 1].end);
+        }
+
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    pub fn remove(&mut self, interval: Interval) {
+        if interval.is_empty() {
+            return;
+        }
+
+        let first = self.intervals.partition_point(|i| i.end <= interval.start);
+        let last = self.intervals.partition_point(|i| i.start < interval.end);
+
+        if first >= last {
+            return;
+        }
+
+        let left = Interval::new(self.intervals[first].start, interval.start);
+        let right = Interval::new(interval.end, self.intervals[last - 1].end);
+
+        let remaining = [left, right].into_iter().filter(|i| !i.is_empty());
+        self.intervals.splice(first..last, remaining);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for &interval in &other.intervals {
+            result.insert(interval);
+        }
+        result
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (self.intervals[i], other.intervals[j]);
+
+            let overlap = a.intersection(&b);
+            if !overlap.is_empty() {
+                intervals.push(overlap);
+            }
+
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        // both inputs are disjoint and non-adjacent, so the overlaps are too
+        Self { intervals }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for &interval in &other.intervals {
+            result.remove(interval);
+        }
+        result
+    }
+
+    // splits into the values `< at` and the values `>= at`
+    pub fn split_at(&self, at: i64) -> (Self, Self) {
+        let i = self.intervals.partition_point(|i| i.end <= at);
+
+        let mut lower = self.intervals[..i].to_vec();
+        let mut upper = Vec::new();
+
+        if let Some(interval) = self.intervals.get(i) {
+            let (l, u) = interval.split_at(at);
+            if !l.is_empty() {
+                lower.push(l);
+            }
+            if !u.is_empty() {
+                upper.push(u);
+            }
+            upper.extend_from_slice(&self.intervals[i + 1..]);
+        }
+
+        (Self { intervals: lower }, Self { intervals: upper })
+    }
+}
+
+// An axis-aligned box in `N` dimensions, one interval per axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HyperRect<const N: usize> {
+    pub axes: [Interval; N],
+}
+
+impl<const N: usize> Index<usize> for HyperRect<N> {
+    type Output = Interval;
+
+    fn index(&self, axis: usize) -> &Self::Output {
+        &self.axes[axis]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for HyperRect<N> {
+    fn index_mut(&mut self, axis: usize) -> &mut Self::Output {
+        &mut self.axes[axis]
+    }
+}
+
+impl<const N: usize> HyperRect<N> {
+    pub const fn new(axes: [Interval; N]) -> Self {
+        Self { axes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.axes.iter().any(Interval::is_empty)
+    }
+
+    // the number of integer points inside the box
+    pub fn volume(&self) -> u64 {
+        self.axes.iter().map(Interval::len).product()
+    }
+
+    pub fn contains(&self, point: [i64; N]) -> bool {
+        self.axes.iter().zip(point).all(|(i, v)| i.contains(v))
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut result = *self;
+        for (axis, interval) in result.axes.iter_mut().enumerate() {
+            *interval = interval.intersection(&other.axes[axis]);
+        }
+        result
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    // splits along `axis` into the box with values `< at` and the box with values `>= at`
+    pub fn split_at(&self, axis: usize, at: i64) -> (Self, Self) {
+        let (l, u) = self.axes[axis].split_at(at);
+
+        let mut lower = *self;
+        let mut upper = *self;
+        lower.axes[axis] = l;
+        upper.axes[axis] = u;
+
+        (lower, upper)
+    }
+
+    // everything in `self` that is not in `other`, as pairwise disjoint boxes
+    pub fn difference(&self, other: &Self) -> Vec<Self> {
+        if !self.intersects(other) {
+            return if self.is_empty() { vec![] } else { vec![*self] };
+        }
+
+        let mut result = Vec::new();
+        let mut rest = *self;
+
+        // peel off the slabs below and above `other` one axis at a time,
+        // what remains in the end is exactly the intersection
+        for axis in 0..N {
+            let (below, r) = rest.split_at(axis, other.axes[axis].start);
+            let (r, above) = r.split_at(axis, other.axes[axis].end);
+            rest = r;
+
+            result.extend([below, above].into_iter().filter(|b| !b.is_empty()));
+        }
+
+        result
+    }
+}
@@ -0,0 +1,115 @@
+use super::*;
+
+fn set(intervals: &[(i64, i64)]) -> IntervalSet {
+    intervals
+        .iter()
+        .map(|&(start, end)| Interval::new(start, end))
+        .collect()
+}
+
+#[test]
+fn interval_basics() {
+    let a = Interval::new(1, 5);
+
+    assert_eq!(a.len(), 4);
+    assert!(a.contains(1));
+    assert!(!a.contains(5));
+    assert_eq!(Interval::inclusive(1, 5).len(), 5);
+    assert_eq!(Interval::inclusive(1, i64::MAX), Interval::new(1, i64::MAX));
+    assert!(Interval::new(5, 1).is_empty());
+    assert_eq!(Interval::new(5, 1).len(), 0);
+
+    assert_eq!(a.intersection(&Interval::new(3, 10)), Interval::new(3, 5));
+    assert!(!a.intersects(&Interval::new(5, 10)));
+
+    assert_eq!(a.split_at(3), (Interval::new(1, 3), Interval::new(3, 5)));
+    assert_eq!(a.split_at(0), (Interval::new(1, 1), Interval::new(1, 5)));
+    assert_eq!(a.split_at(10), (Interval::new(1, 5), Interval::new(5, 5)));
+
+    assert_eq!(a.difference(&Interval::new(2, 3)), set(&[(1, 2), (3, 5)]));
+}
+
+#[test]
+fn set_insert_merges() {
+    let s = set(&[(1, 3), (5, 7), (3, 5), (10, 12)]);
+
+    assert_eq!(s.intervals(), &[Interval::new(1, 7), Interval::new(10, 12)]);
+    assert_eq!(s.len(), 8);
+    assert!(s.contains(6));
+    assert!(!s.contains(7));
+    assert!(s.contains(10));
+}
+
+#[test]
+fn set_remove() {
+    let mut s = set(&[(0, 10), (20, 30)]);
+    s.remove(Interval::new(5, 25));
+
+    assert_eq!(s, set(&[(0, 5), (25, 30)]));
+
+    s.remove(Interval::new(-10, 100));
+    assert!(s.is_empty());
+}
+
+#[test]
+fn set_operations() {
+    let a = set(&[(0, 10), (20, 30)]);
+    let b = set(&[(5, 25), (28, 40)]);
+
+    assert_eq!(a.union(&b), set(&[(0, 40)]));
+    assert_eq!(a.intersection(&b), set(&[(5, 10), (20, 25), (28, 30)]));
+    assert_eq!(a.difference(&b), set(&[(0, 5), (25, 28)]));
+    assert_eq!(b.difference(&a), set(&[(10, 20), (30, 40)]));
+}
+
+#[test]
+fn set_split_at() {
+    let s = set(&[(0, 10), (20, 30)]);
+
+    assert_eq!(s.split_at(5), (set(&[(0, 5)]), set(&[(5, 10), (20, 30)])));
+    assert_eq!(s.split_at(15), (set(&[(0, 10)]), set(&[(20, 30)])));
+    assert_eq!(s.split_at(-5), (set(&[]), s.clone()));
+    assert_eq!(s.split_at(50), (s.clone(), set(&[])));
+}
+
+#[test]
+fn hyperrect_volume_and_split() {
+    let r = HyperRect::new([Interval::new(0, 4), Interval::new(0, 3)]);
+
+    assert_eq!(r.volume(), 12);
+    assert!(r.contains([3, 2]));
+    assert!(!r.contains([4, 2]));
+
+    let (lower, upper) = r.split_at(0, 1);
+    assert_eq!(lower.volume(), 3);
+    assert_eq!(upper.volume(), 9);
+}
+
+#[test]
+fn hyperrect_difference() {
+    let a = HyperRect::new([
+        Interval::new(0, 10),
+        Interval::new(0, 10),
+        Interval::new(0, 10),
+    ]);
+    let b = HyperRect::new([
+        Interval::new(2, 5),
+        Interval::new(-5, 5),
+        Interval::new(8, 20),
+    ]);
+
+    let difference = a.difference(&b);
+    let overlap = a.intersection(&b);
+
+    let total: u64 = difference.iter().map(HyperRect::volume).sum();
+    assert_eq!(total, a.volume() - overlap.volume());
+
+    for (i, x) in difference.iter().enumerate() {
+        assert!(!x.intersects(&b));
+        for y in &difference[i + 1..] {
+            assert!(!x.intersects(y));
+        }
+    }
+
+    assert_eq!(a.difference(&a), vec![]);
+}